@@ -0,0 +1,270 @@
+//! Burn-in protection for static images.
+//!
+//! OLED pixels age in proportion to how long and how brightly they are lit, so a UI that shows
+//! the same image for hours leaves a permanent ghost of it. Burn-in protection spreads the wear
+//! by slowly moving the image around a few pixels, and can also invert the display after long
+//! periods without a new frame.
+//!
+//! - Vertical shifts move the display start line, so they take effect immediately and don't
+//!   require the image to be redrawn. The rows shifted into view come from the display RAM
+//!   outside of the image, which must be blank (enable `Config::clear_on_init`): the image height
+//!   plus `max_shift_rows` must not exceed 128. With `Config::page_flipping`, the start line
+//!   selects the page being shown instead, so the image is not shifted vertically. When the
+//!   display is scrolled (see `SSD1322::scroll_to`), the rows shifted into view are the
+//!   neighbouring rows of the scroll buffer.
+//! - Horizontal shifts move the column addresses the image is written to, so they take effect on
+//!   the next `flush_frame`, which also clears the columns the image moved away from. The
+//!   display RAM only addresses columns in groups of 4 pixels, so horizontal shifts are in steps of
+//!   4 pixels.
+//!
+//! Shifts are described for `Orientation::Standard`: with the other orientations the image moves
+//! left and/or up instead, as the orientation mirrors it.
+//!
+//! The application keeps drawing in its own coordinate space; the shifts are applied by the
+//! driver. The driver has no clock, so time is reported with `SSD1322::tick`.
+//!
+//! # Example
+//!
+//! ```
+//! use ssd1322_rs::burn_in::BurnInProtection;
+//! use ssd1322_rs::Config;
+//!
+//! let config = Config {
+//!     burn_in: Some(BurnInProtection {
+//!         shift_interval_ms: 60_000,
+//!         max_shift_columns: 1,
+//!         max_shift_rows: 2,
+//!         invert_after_ms: Some(3_600_000),
+//!     }),
+//!     ..Config::default()
+//! };
+//! ```
+
+use crate::instruction::{Command, DisplayMode};
+use crate::{Error, SSD1322};
+use core::convert::Infallible;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiDevice;
+
+/// Burn-in protection settings, set in `Config::burn_in`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BurnInProtection {
+    /// Time between shifts of the image, in milliseconds.
+    pub shift_interval_ms: u32,
+    /// How far the image is shifted right, at most, in column addresses of 4 pixels. 0 disables
+    /// horizontal shifting. Shifts are clamped to the display RAM columns the image leaves unused,
+    /// so a 480 pixel wide image is never shifted horizontally.
+    pub max_shift_columns: u8,
+    /// How far the image is shifted down, at most, in rows. 0 disables vertical shifting.
+    pub max_shift_rows: u8,
+    /// Time without a flush after which the display is inverted, and then inverted back, every
+    /// `invert_after_ms`, in milliseconds. The next flush restores it. `None` never inverts the
+    /// display.
+    pub invert_after_ms: Option<u32>,
+}
+
+impl BurnInProtection {
+    // Returns the shift for a step in the cycle, in column addresses and rows.
+    //
+    // The shifts visit every position in the range once per cycle, moving back and forth along each
+    // row of positions so that each step moves the image by a single column or row.
+    fn shift(&self, step: u32) -> (u8, u8) {
+        let columns = self.max_shift_columns as u32 + 1;
+        let row = step / columns;
+        let column = if row % 2 == 0 {
+            step % columns
+        } else {
+            columns - 1 - step % columns
+        };
+        (column as u8, row as u8)
+    }
+
+    // Returns the number of steps in the cycle of shifts, up to 256 * 256
+    fn steps(&self) -> u32 {
+        (self.max_shift_columns as u32 + 1) * (self.max_shift_rows as u32 + 1)
+    }
+}
+
+impl<SPI, DC, RST, PO, E> SSD1322<SPI, DC, RST, PO>
+where
+    SPI: SpiDevice<Error = E>,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    PO: OutputPin<Error = Infallible>,
+{
+    /// Returns the current burn-in protection shift of the image, in column addresses of 4 pixels
+    /// to the right and rows down.
+    pub fn burn_in_shift(&self) -> (u8, u8) {
+        (self.shift_columns[self.front_page as usize], self.shift_rows)
+    }
+
+    // Returns how far a `width` pixel wide image can be shifted right before it runs off the end
+    // of the display RAM, in column addresses. Larger shifts are clamped to this.
+    #[cfg(feature = "frame")]
+    pub(crate) fn spare_shift_columns(width: u16) -> u8 {
+        let base = 480u16.saturating_sub(width) / 2 / 4;
+        120u16.saturating_sub(base + width / 4) as u8
+    }
+
+    // Shifts or inverts the image as time passes
+    pub(crate) async fn apply_burn_in(
+        &mut self,
+        protection: BurnInProtection,
+        elapsed_ms: u32,
+    ) -> Result<(), Error<E>> {
+        self.shift_ms = self.shift_ms.saturating_add(elapsed_ms);
+        if protection.shift_interval_ms > 0 && self.shift_ms >= protection.shift_interval_ms {
+            self.shift_ms = 0;
+            self.shift_step = (self.shift_step + 1) % protection.steps();
+            let (columns, rows) = protection.shift(self.shift_step);
+            self.pending_shift_columns = columns;
+            // The start line selects the page being shown when page flipping
+            self.shift_rows = if self.page_flipping { 0 } else { rows };
+            self.update_start_line().await?;
+        }
+
+        if let Some(invert_after_ms) = protection.invert_after_ms.filter(|&ms| ms > 0) {
+            let inverted = (self.idle_ms / invert_after_ms) % 2 == 1;
+            if inverted != self.burn_in_inverted && self.blanked.is_none() {
+                self.write_burn_in_mode(inverted).await?;
+            }
+        }
+        Ok(())
+    }
+
+    // Undoes the burn-in protection's inversion, called whenever the display is flushed
+    pub(crate) async fn restore_burn_in_inversion(&mut self) -> Result<(), Error<E>> {
+        if self.burn_in_inverted && self.blanked.is_none() {
+            self.write_burn_in_mode(false).await?;
+        }
+        self.burn_in_inverted = false;
+        Ok(())
+    }
+
+    // Sends the display mode, inverted from the configured one if `inverted`
+    async fn write_burn_in_mode(&mut self, inverted: bool) -> Result<(), Error<E>> {
+        let mode = match (self.display_mode(), inverted) {
+            (DisplayMode::Normal, true) => DisplayMode::Inverse,
+            (DisplayMode::Inverse, true) => DisplayMode::Normal,
+            (mode, _) => mode,
+        };
+        self.write_command(&Command::SetDisplayMode(mode).prepare()?).await?;
+        self.burn_in_inverted = inverted;
+        Ok(())
+    }
+
+    // Clears the column addresses a `width` pixel wide image in `page` of the display RAM no longer
+    // covers after moving from its previous shift to `shift` column addresses right, in `height`
+    // rows from `start_row`
+    #[cfg(feature = "frame")]
+    pub(crate) async fn clear_uncovered_columns(
+        &mut self,
+        width: u16,
+        start_row: u16,
+        height: u16,
+        page: u8,
+        shift: u8,
+    ) -> Result<(), Error<E>> {
+        let base = (480 - width) / 2 / 4;
+        let (old, new) = (self.shift_columns[page as usize] as u16, shift as u16);
+        if old == new {
+            return Ok(());
+        }
+        let (start, end) = if new > old {
+            (base + old, base + new - 1)
+        } else {
+            (base + width / 4 + new, base + width / 4 + old - 1)
+        };
+        // Columns past the end of the display RAM never held any of the image
+        let end = end.min(crate::instruction::consts::BUF_COL_MAX as u16);
+        if start > end {
+            self.shift_columns[page as usize] = shift;
+            return Ok(());
+        }
+
+        self.write_command(&Command::SetColumnAddress(start as u8, end as u8).prepare()?).await?;
+        self.write_command(&Command::SetRowAddress(start_row as u8, (start_row + height - 1) as u8).prepare()?).await?;
+        self.write_command(&Command::WriteRam.prepare()?).await?;
+        // Each column address holds 4 pixels in 2 bytes
+        let zeros = [0u8; 64];
+        let mut remaining = (end - start + 1) as usize * 2 * height as usize;
+        while remaining > 0 {
+            let len = remaining.min(zeros.len());
+            self.write_data(&zeros[..len]).await?;
+            remaining -= len;
+        }
+        self.shift_columns[page as usize] = shift;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn protection(max_shift_columns: u8, max_shift_rows: u8) -> BurnInProtection {
+        BurnInProtection { shift_interval_ms: 1, max_shift_columns, max_shift_rows, invert_after_ms: None }
+    }
+
+    #[test]
+    fn largest_cycle_does_not_overflow() {
+        let protection = protection(255, 255);
+        assert_eq!(protection.steps(), 256 * 256);
+        assert_eq!(protection.shift(protection.steps() - 1), (0, 255));
+    }
+
+    #[test]
+    fn shifts_visit_every_position_one_step_apart() {
+        let protection = protection(2, 1);
+        let shifts: [(u8, u8); 6] = core::array::from_fn(|step| protection.shift(step as u32));
+        assert_eq!(shifts, [(0, 0), (1, 0), (2, 0), (2, 1), (1, 1), (0, 1)]);
+    }
+
+    #[cfg(all(feature = "testing", feature = "frame"))]
+    #[test]
+    fn shift_columns_are_clamped_to_the_unused_display_ram() {
+        use crate::testing::{MockPin, MockSpi};
+        type Display = SSD1322<MockSpi, MockPin, MockPin, MockPin>;
+        assert_eq!(Display::spare_shift_columns(480), 0);
+        assert_eq!(Display::spare_shift_columns(256), 28);
+        assert_eq!(Display::spare_shift_columns(472), 1);
+    }
+
+    #[cfg(all(feature = "testing", feature = "frame"))]
+    #[test]
+    fn full_width_frame_flushes_while_shifting() {
+        use crate::testing::{block_on, Recorder};
+        use crate::{Config, Frame};
+        let recorder = Recorder::new();
+        let config = Config { burn_in: Some(protection(4, 0)), ..Config::default() };
+        let mut display = recorder.display(config);
+        block_on(display.init_default(&mut recorder.delay())).unwrap();
+        let frame = Frame::new(480, 64, [0u8; 480 * 64 / 2]);
+        for _ in 0..4 {
+            block_on(display.tick(1)).unwrap();
+            block_on(display.flush_frame(&frame)).unwrap();
+        }
+        assert_eq!(display.burn_in_shift().0, 0);
+    }
+
+    #[cfg(all(feature = "testing", feature = "frame"))]
+    #[test]
+    fn uncovered_columns_stay_within_the_display_ram() {
+        use crate::testing::{block_on, Recorder, Transfer};
+        use crate::Config;
+        let recorder = Recorder::new();
+        let mut display = recorder.display(Config::default());
+
+        // An unchanged shift clears nothing
+        block_on(display.clear_uncovered_columns(256, 0, 64, 0, 0)).unwrap();
+        assert!(recorder.events().is_empty());
+
+        // Columns past the end of the display RAM are skipped
+        block_on(display.clear_uncovered_columns(472, 0, 64, 0, 3)).unwrap();
+        assert_eq!(recorder.transfers()[0], Transfer::Command(Command::SetColumnAddress(1, 3)));
+        recorder.clear();
+        block_on(display.clear_uncovered_columns(472, 0, 64, 0, 0)).unwrap();
+        assert_eq!(recorder.transfers()[0], Transfer::Command(Command::SetColumnAddress(119, 119)));
+    }
+}
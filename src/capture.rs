@@ -0,0 +1,677 @@
+//! Capture and replay of the traffic sent to the display.
+//!
+//! This module defines a compact binary format recording everything the driver sends: command
+//! bytes, data bytes, data/command pin transitions and delays. `CaptureSpi`, `CaptureDc` and
+//! `CaptureDelay` wrap the driver's SPI device, data/command pin and delay respectively, and record
+//! into a shared `Capture` buffer. `SSD1322::replay_capture` streams a capture back through a
+//! driver, which can reproduce a field bug on a bench panel, or on a host with the mocks in the
+//! `testing` module and the emulator in the `emulator` module.
+//!
+//! The capture buffer is a caller-provided byte slice, so capturing works without an allocator.
+//! When the buffer is full, recording stops and `Capture::overflowed` returns `true`; the records
+//! captured up to that point remain valid.
+//!
+//! # Format
+//!
+//! A capture starts with the 4 byte magic `SSDC` followed by a version byte (currently 1), and
+//! then a sequence of records, each starting with a tag byte:
+//!
+//! | Tag    | Payload                          | Meaning                                     |
+//! |--------|----------------------------------|---------------------------------------------|
+//! | `0x01` | length (u8), bytes               | Bytes written with the data/command pin low |
+//! | `0x02` | length (u16 LE), bytes           | Bytes written with the data/command pin high|
+//! | `0x03` | nanoseconds (u32 LE)             | A delay                                     |
+//! | `0x04` | level (u8, 0 or 1)               | The data/command pin changed level          |
+//!
+//! Consecutive writes with the same pin level are merged into one record where they fit.
+//!
+//! > **This module is only available when the `capture` feature is enabled.**
+//!
+//! # Example
+//!
+//! ```
+//! # use core::cell::RefCell;
+//! # use core::convert::Infallible;
+//! # use embedded_hal::digital::OutputPin;
+//! # use embedded_hal_async::delay::DelayNs;
+//! # use embedded_hal_async::spi::SpiDevice;
+//! use ssd1322_rs::capture::{Capture, CaptureDc, CaptureDelay, CaptureSpi};
+//! use ssd1322_rs::{Config, Error, SSD1322};
+//!
+//! async fn capture_init<SPI, DC, RST, PO, D>(
+//!     spi: SPI,
+//!     dc: DC,
+//!     rst: RST,
+//!     po: PO,
+//!     delay: D,
+//!     buf: &mut [u8],
+//! ) -> Result<usize, Error<SPI::Error>>
+//! where
+//!     SPI: SpiDevice,
+//!     DC: OutputPin<Error = Infallible>,
+//!     RST: OutputPin<Error = Infallible>,
+//!     PO: OutputPin<Error = Infallible>,
+//!     D: DelayNs,
+//! {
+//!     let capture = RefCell::new(Capture::new(buf));
+//!     let mut display = SSD1322::new(
+//!         CaptureSpi::new(spi, &capture),
+//!         CaptureDc::new(dc, &capture),
+//!         rst,
+//!         po,
+//!         Config::default(),
+//!     );
+//!     display.init_default(&mut CaptureDelay::new(delay, &capture)).await?;
+//!     // The capture can now be stored, or sent over a debug link
+//!     let len = capture.borrow().as_bytes().len();
+//!     Ok(len)
+//! }
+//! ```
+
+use crate::{Error, SSD1322};
+use core::cell::RefCell;
+use core::convert::Infallible;
+use embedded_hal::digital::{ErrorType as PinErrorType, OutputPin};
+use embedded_hal::spi::{ErrorType as SpiErrorType, Operation};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::SpiDevice;
+
+/// The magic bytes at the start of every capture.
+pub const MAGIC: [u8; 4] = *b"SSDC";
+
+/// The version of the capture format written by this crate.
+pub const VERSION: u8 = 1;
+
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+const TAG_COMMAND: u8 = 0x01;
+const TAG_DATA: u8 = 0x02;
+const TAG_DELAY: u8 = 0x03;
+const TAG_DC: u8 = 0x04;
+
+/// Errors that can occur when reading a capture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureError {
+    /// The capture does not start with the expected magic bytes and version.
+    BadHeader,
+    /// The capture ends part way through a record.
+    Truncated,
+    /// The capture contains a record with an unknown tag.
+    UnknownRecord(u8),
+}
+
+/// A single record of a capture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Record<'a> {
+    /// Bytes written with the data/command pin low.
+    Command(&'a [u8]),
+    /// Bytes written with the data/command pin high.
+    Data(&'a [u8]),
+    /// A delay, in nanoseconds.
+    Delay(u32),
+    /// The data/command pin changed to the given level (`true` for high).
+    Dc(bool),
+}
+
+/// A capture being recorded into a caller-provided buffer.
+///
+/// The capture is shared between the wrappers in this module through a `RefCell`.
+pub struct Capture<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+    /// Last level the data/command pin was set to.
+    dc: bool,
+    /// Start of the last record if it is a write that more bytes could be merged into.
+    last_write: Option<usize>,
+    overflowed: bool,
+}
+
+impl<'a> Capture<'a> {
+    /// Starts a new capture in `buf`.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        let mut capture = Self {
+            buf,
+            len: 0,
+            dc: false,
+            last_write: None,
+            overflowed: false,
+        };
+        capture.clear();
+        capture
+    }
+
+    /// Discards all recorded traffic, keeping only the header.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.last_write = None;
+        self.overflowed = false;
+        let mut header = [0u8; HEADER_LEN];
+        header[..MAGIC.len()].copy_from_slice(&MAGIC);
+        header[MAGIC.len()] = VERSION;
+        self.push(&header);
+    }
+
+    /// The capture recorded so far, including the header.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Whether the buffer filled up, so that some traffic was not recorded.
+    pub fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Appends `bytes` if they fit, otherwise marks the capture as overflowed.
+    fn push(&mut self, bytes: &[u8]) -> bool {
+        if self.overflowed || self.buf.len() - self.len < bytes.len() {
+            self.overflowed = true;
+            return false;
+        }
+        self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+        true
+    }
+
+    /// Records bytes written with the data/command pin at level `dc`.
+    fn write(&mut self, dc: bool, mut bytes: &[u8]) {
+        let (tag, max) = if dc {
+            (TAG_DATA, u16::MAX as usize)
+        } else {
+            (TAG_COMMAND, u8::MAX as usize)
+        };
+
+        // Merge into the previous record where possible
+        if let Some(start) = self.last_write {
+            if self.buf[start] == tag {
+                let len = self.record_len(start);
+                let n = bytes.len().min(max - len);
+                let (head, tail) = bytes.split_at(n);
+                if self.push(head) {
+                    self.set_record_len(start, len + n);
+                }
+                bytes = tail;
+            }
+        }
+
+        for chunk in bytes.chunks(max) {
+            let start = self.len;
+            let header_ok = if dc {
+                self.push(&[tag, 0, 0])
+            } else {
+                self.push(&[tag, 0])
+            };
+            if !header_ok || !self.push(chunk) {
+                // Drop the partial record
+                self.len = start;
+                self.last_write = None;
+                return;
+            }
+            self.set_record_len(start, chunk.len());
+            self.last_write = Some(start);
+        }
+    }
+
+    fn record_len(&self, start: usize) -> usize {
+        match self.buf[start] {
+            TAG_DATA => u16::from_le_bytes([self.buf[start + 1], self.buf[start + 2]]) as usize,
+            _ => self.buf[start + 1] as usize,
+        }
+    }
+
+    fn set_record_len(&mut self, start: usize, len: usize) {
+        match self.buf[start] {
+            TAG_DATA => self.buf[start + 1..start + 3].copy_from_slice(&(len as u16).to_le_bytes()),
+            _ => self.buf[start + 1] = len as u8,
+        }
+    }
+
+    /// Records a delay, splitting delays too long for one record.
+    fn delay(&mut self, mut ns: u64) {
+        self.last_write = None;
+        while ns > 0 {
+            let chunk = ns.min(u32::MAX as u64) as u32;
+            let [a, b, c, d] = chunk.to_le_bytes();
+            self.push(&[TAG_DELAY, a, b, c, d]);
+            ns -= chunk as u64;
+        }
+    }
+
+    /// Records the data/command pin being set, if its level changed.
+    fn set_dc(&mut self, level: bool) {
+        if level != self.dc {
+            self.dc = level;
+            self.last_write = None;
+            self.push(&[TAG_DC, level as u8]);
+        }
+    }
+}
+
+/// Iterates over the records of a capture.
+pub struct Records<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Records<'a> {
+    /// Checks the header of `capture` and returns an iterator over its records.
+    pub fn new(capture: &'a [u8]) -> Result<Self, CaptureError> {
+        if capture.len() < HEADER_LEN {
+            return Err(CaptureError::BadHeader);
+        }
+        let (header, data) = capture.split_at(HEADER_LEN);
+        if header[..MAGIC.len()] != MAGIC || header[MAGIC.len()] != VERSION {
+            return Err(CaptureError::BadHeader);
+        }
+        Ok(Self { data })
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CaptureError> {
+        if self.data.len() < n {
+            return Err(CaptureError::Truncated);
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Result<Record<'a>, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&tag, rest) = self.data.split_first()?;
+        self.data = rest;
+        let record = match tag {
+            TAG_COMMAND => self
+                .take(1)
+                .and_then(|len| self.take(len[0] as usize))
+                .map(Record::Command),
+            TAG_DATA => self
+                .take(2)
+                .and_then(|len| self.take(u16::from_le_bytes([len[0], len[1]]) as usize))
+                .map(Record::Data),
+            TAG_DELAY => self
+                .take(4)
+                .map(|ns| Record::Delay(u32::from_le_bytes([ns[0], ns[1], ns[2], ns[3]]))),
+            TAG_DC => self.take(1).map(|level| Record::Dc(level[0] != 0)),
+            _ => Err(CaptureError::UnknownRecord(tag)),
+        };
+        if record.is_err() {
+            // Stop iterating after an error
+            self.data = &[];
+        }
+        Some(record)
+    }
+}
+
+/// Errors that can occur when replaying a capture.
+#[derive(Debug)]
+pub enum ReplayError<E> {
+    /// The capture could not be read.
+    Capture(CaptureError),
+    /// The driver failed to send the replayed traffic.
+    Driver(Error<E>),
+}
+
+impl<E> From<CaptureError> for ReplayError<E> {
+    fn from(err: CaptureError) -> Self {
+        ReplayError::Capture(err)
+    }
+}
+
+impl<E> From<Error<E>> for ReplayError<E> {
+    fn from(err: Error<E>) -> Self {
+        ReplayError::Driver(err)
+    }
+}
+
+impl<SPI, DC, RST, PO, E> SSD1322<SPI, DC, RST, PO>
+where
+    SPI: SpiDevice<Error = E>,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    PO: OutputPin<Error = Infallible>,
+{
+    /// Replays a capture recorded with the wrappers in this module through this driver.
+    ///
+    /// Command bytes are sent with the data/command pin low, data bytes with it high, and delays
+    /// are reproduced with `delay`. Data/command pin records carry no bytes and are skipped, since
+    /// the driver sets the pin itself. The capture is checked as it is replayed, so a corrupt
+    /// capture may be partly replayed before an error is returned.
+    ///
+    /// > **This function is only available when the `capture` feature is enabled.**
+    ///
+    /// # Parameters
+    ///
+    /// - `capture`: The capture to replay, including its header.
+    /// - `delay`: A mutable reference to an implementation of the `DelayNs` trait.
+    ///
+    /// # Errors
+    ///
+    /// - `ReplayError::Capture`: If the capture is not valid.
+    /// - `ReplayError::Driver`: If sending the replayed traffic fails.
+    pub async fn replay_capture<D>(
+        &mut self,
+        capture: &[u8],
+        delay: &mut D,
+    ) -> Result<(), ReplayError<E>>
+    where
+        D: DelayNs,
+    {
+        for record in Records::new(capture)? {
+            match record? {
+                Record::Command(cmds) => {
+                    for &cmd in cmds {
+                        self.write_raw_command(cmd, &[]).await?;
+                    }
+                }
+                Record::Data(data) => self.write_data(data).await?,
+                Record::Delay(ns) => delay.delay_ns(ns).await,
+                Record::Dc(_) => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An `SpiDevice` wrapper which records every byte written into a `Capture`, along with the level
+/// of the data/command pin recorded by `CaptureDc`.
+pub struct CaptureSpi<'c, 'a, SPI> {
+    inner: SPI,
+    capture: &'c RefCell<Capture<'a>>,
+}
+
+impl<'c, 'a, SPI> CaptureSpi<'c, 'a, SPI> {
+    /// Wraps `inner`, recording into `capture`.
+    pub fn new(inner: SPI, capture: &'c RefCell<Capture<'a>>) -> Self {
+        Self { inner, capture }
+    }
+
+    /// Returns the wrapped SPI device.
+    pub fn into_inner(self) -> SPI {
+        self.inner
+    }
+}
+
+impl<SPI: SpiDevice> SpiErrorType for CaptureSpi<'_, '_, SPI> {
+    type Error = SPI::Error;
+}
+
+impl<SPI: SpiDevice> SpiDevice for CaptureSpi<'_, '_, SPI> {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        // Record before the transaction, as `TransferInPlace` replaces the bytes written with the
+        // bytes read
+        {
+            let mut capture = self.capture.borrow_mut();
+            let dc = capture.dc;
+            for operation in operations.iter() {
+                match operation {
+                    Operation::Write(buf) => capture.write(dc, buf),
+                    Operation::Transfer(_, buf) => capture.write(dc, buf),
+                    Operation::TransferInPlace(buf) => capture.write(dc, buf),
+                    Operation::DelayNs(ns) => capture.delay(*ns as u64),
+                    Operation::Read(_) => (),
+                }
+            }
+        }
+        self.inner.transaction(operations).await
+    }
+}
+
+/// An `OutputPin` wrapper for the data/command pin, which records its level into a `Capture`.
+pub struct CaptureDc<'c, 'a, P> {
+    inner: P,
+    capture: &'c RefCell<Capture<'a>>,
+}
+
+impl<'c, 'a, P> CaptureDc<'c, 'a, P> {
+    /// Wraps `inner`, recording into `capture`.
+    pub fn new(inner: P, capture: &'c RefCell<Capture<'a>>) -> Self {
+        Self { inner, capture }
+    }
+
+    /// Returns the wrapped pin.
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+}
+
+impl<P: OutputPin<Error = Infallible>> PinErrorType for CaptureDc<'_, '_, P> {
+    type Error = Infallible;
+}
+
+impl<P: OutputPin<Error = Infallible>> OutputPin for CaptureDc<'_, '_, P> {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.capture.borrow_mut().set_dc(false);
+        self.inner.set_low()
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.capture.borrow_mut().set_dc(true);
+        self.inner.set_high()
+    }
+}
+
+/// A `DelayNs` wrapper which records every delay into a `Capture`.
+pub struct CaptureDelay<'c, 'a, D> {
+    inner: D,
+    capture: &'c RefCell<Capture<'a>>,
+}
+
+impl<'c, 'a, D> CaptureDelay<'c, 'a, D> {
+    /// Wraps `inner`, recording into `capture`.
+    pub fn new(inner: D, capture: &'c RefCell<Capture<'a>>) -> Self {
+        Self { inner, capture }
+    }
+
+    /// Returns the wrapped delay.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+impl<D: DelayNs> DelayNs for CaptureDelay<'_, '_, D> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.capture.borrow_mut().delay(ns as u64);
+        self.inner.delay_ns(ns).await
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.capture.borrow_mut().delay(us as u64 * 1_000);
+        self.inner.delay_us(us).await
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.capture.borrow_mut().delay(ms as u64 * 1_000_000);
+        self.inner.delay_ms(ms).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    fn header() -> Vec<u8> {
+        let mut capture = Vec::from(MAGIC);
+        capture.push(VERSION);
+        capture
+    }
+
+    #[test]
+    fn records_are_read_back_in_order() {
+        let mut buf = [0u8; 64];
+        let mut capture = Capture::new(&mut buf);
+        capture.write(false, &[0xAF]);
+        capture.write(false, &[0xA6]);
+        capture.set_dc(true);
+        capture.write(true, &[1, 2]);
+        capture.write(true, &[3]);
+        capture.delay(1_000);
+        capture.set_dc(false);
+
+        let records: Result<Vec<_>, _> = Records::new(capture.as_bytes()).unwrap().collect();
+        assert_eq!(
+            records.unwrap(),
+            [
+                Record::Command(&[0xAF, 0xA6]),
+                Record::Dc(true),
+                Record::Data(&[1, 2, 3]),
+                Record::Delay(1_000),
+                Record::Dc(false),
+            ]
+        );
+        assert!(!capture.overflowed());
+    }
+
+    #[test]
+    fn overflow_keeps_the_records_that_fit() {
+        let mut buf = [0u8; HEADER_LEN + 4];
+        let mut capture = Capture::new(&mut buf);
+        capture.write(false, &[0xAF]);
+        capture.write(true, &[0; 8]);
+        assert!(capture.overflowed());
+
+        let records: Result<Vec<_>, _> = Records::new(capture.as_bytes()).unwrap().collect();
+        assert_eq!(records.unwrap(), [Record::Command(&[0xAF])]);
+    }
+
+    #[test]
+    fn bad_header_is_rejected() {
+        let mut capture = header();
+        capture[0] = b'X';
+        assert!(matches!(Records::new(&capture), Err(CaptureError::BadHeader)));
+
+        let mut capture = header();
+        capture[MAGIC.len()] = VERSION + 1;
+        assert!(matches!(Records::new(&capture), Err(CaptureError::BadHeader)));
+
+        assert!(matches!(Records::new(&MAGIC), Err(CaptureError::BadHeader)));
+    }
+
+    #[test]
+    fn truncated_and_unknown_records_stop_iteration() {
+        let mut capture = header();
+        capture.extend_from_slice(&[TAG_COMMAND, 1, 0xAF, TAG_DATA, 4, 0, 1, 2]);
+        let records: Vec<_> = Records::new(&capture).unwrap().collect();
+        assert_eq!(records, [Ok(Record::Command(&[0xAF])), Err(CaptureError::Truncated)]);
+
+        let mut capture = header();
+        capture.extend_from_slice(&[TAG_DELAY, 0, 0]);
+        let records: Vec<_> = Records::new(&capture).unwrap().collect();
+        assert_eq!(records, [Err(CaptureError::Truncated)]);
+
+        let mut capture = header();
+        capture.extend_from_slice(&[0x7F, TAG_DC, 1]);
+        let records: Vec<_> = Records::new(&capture).unwrap().collect();
+        assert_eq!(records, [Err(CaptureError::UnknownRecord(0x7F))]);
+    }
+
+    #[cfg(feature = "testing")]
+    mod transfers {
+        use super::*;
+        use crate::testing::block_on;
+
+        // An SPI device which reads back 0xFF for every byte transferred
+        struct PullUp;
+
+        impl SpiErrorType for PullUp {
+            type Error = Infallible;
+        }
+
+        impl SpiDevice for PullUp {
+            async fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+                for operation in operations.iter_mut() {
+                    match operation {
+                        Operation::Read(buf) | Operation::TransferInPlace(buf) => buf.fill(0xFF),
+                        Operation::Transfer(read, _) => read.fill(0xFF),
+                        Operation::Write(_) | Operation::DelayNs(_) => (),
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn transfers_record_the_bytes_written() {
+            let mut buf = [0u8; 64];
+            let capture = RefCell::new(Capture::new(&mut buf));
+            let mut spi = CaptureSpi::new(PullUp, &capture);
+            let mut in_place = [0xAF, 0xA6];
+            let (mut transferred, mut read) = ([0u8; 1], [0u8; 1]);
+            block_on(spi.transaction(&mut [
+                Operation::TransferInPlace(&mut in_place),
+                Operation::Transfer(&mut transferred, &[0xA4]),
+                Operation::Read(&mut read),
+            ]))
+            .unwrap();
+            assert_eq!(in_place, [0xFF, 0xFF]);
+
+            let capture = capture.borrow();
+            let records: Result<Vec<_>, _> = Records::new(capture.as_bytes()).unwrap().collect();
+            assert_eq!(records.unwrap(), [Record::Command(&[0xAF, 0xA6, 0xA4])]);
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    mod replay {
+        use super::*;
+        use crate::testing::{block_on, Recorder};
+        use crate::Config;
+        use std::vec;
+
+        // Captures the traffic of `init_default` on the mocks of `recorder`
+        fn capture_init(recorder: &Recorder, buf: &mut [u8]) -> usize {
+            let capture = RefCell::new(Capture::new(buf));
+            let mut display = SSD1322::new(
+                CaptureSpi::new(recorder.spi(), &capture),
+                CaptureDc::new(recorder.dc(), &capture),
+                recorder.rst(),
+                recorder.power(),
+                Config { clear_on_init: false, ..Config::default() },
+            );
+            block_on(display.init_default(&mut CaptureDelay::new(recorder.delay(), &capture))).unwrap();
+            let capture = capture.borrow();
+            assert!(!capture.overflowed());
+            capture.as_bytes().len()
+        }
+
+        #[test]
+        fn replay_reproduces_the_captured_traffic() {
+            let original = Recorder::new();
+            let mut buf = vec![0u8; 4096];
+            let len = capture_init(&original, &mut buf);
+
+            let replayed = Recorder::new();
+            let mut display = replayed.display(Config::default());
+            block_on(display.replay_capture(&buf[..len], &mut replayed.delay())).unwrap();
+
+            assert_eq!(replayed.bytes(), original.bytes());
+            assert_eq!(replayed.transfers(), original.transfers());
+            assert_eq!(replayed.delays(), original.delays());
+        }
+
+        #[test]
+        fn replay_rejects_bad_captures() {
+            let original = Recorder::new();
+            let mut buf = vec![0u8; 4096];
+            let len = capture_init(&original, &mut buf);
+
+            let replayed = Recorder::new();
+            let mut display = replayed.display(Config::default());
+            let result = block_on(display.replay_capture(&buf[..len - 1], &mut replayed.delay()));
+            assert!(matches!(result, Err(ReplayError::Capture(CaptureError::Truncated))));
+
+            let replayed = Recorder::new();
+            let mut display = replayed.display(Config::default());
+            buf[0] = 0;
+            let result = block_on(display.replay_capture(&buf[..len], &mut replayed.delay()));
+            assert!(matches!(result, Err(ReplayError::Capture(CaptureError::BadHeader))));
+            assert!(replayed.events().is_empty());
+        }
+    }
+}
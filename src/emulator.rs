@@ -0,0 +1,760 @@
+//! Software emulator of the SSD1322 controller.
+//!
+//! The `Emulator` consumes the same command and data byte stream that the driver produces, models
+//! the controller's 480x128 4bpp display RAM and registers, and renders the image that a panel
+//! wired to it would show. It is intended for testing orientation, offset and partial update logic
+//! on a host, without hardware.
+//!
+//! The emulator models the panel as `width` pixels wide, centred on the 480 segment lines in the
+//! same way as `SSD1322::set_address_window`, and `height` pixels tall. The panel is assumed to be
+//! mounted such that `Orientation::Standard` shows the image upright: its bottom row is wired to
+//! `COM0`, as the datasheet's `COM[N-1]` to `COM0` scan (`ComScanDirection::RowZeroLast`) shows
+//! row 0 of the display RAM on `COM[N-1]`, where N is the MUX ratio.
+//!
+//! > **This module is only available when the `emulator` feature is enabled.**
+//!
+//! # Example
+//!
+//! ```
+//! use ssd1322_rs::emulator::Emulator;
+//! use ssd1322_rs::testing::{block_on, Recorder};
+//! use ssd1322_rs::Config;
+//!
+//! let recorder = Recorder::new();
+//! let mut display = recorder.display(Config::default());
+//! // A 256 pixel row, 2 pixels per byte, with pixel 3 at full brightness
+//! let mut row = [0u8; 128];
+//! row[1] = 0x0F;
+//!
+//! block_on(async {
+//!     display.init_default(&mut recorder.delay()).await?;
+//!     display.write_rows(1, 256, &row).await
+//! })
+//! .unwrap();
+//!
+//! let mut emulator = Emulator::new(256, 64);
+//! emulator.feed(&recorder.events());
+//! assert_eq!(emulator.pixel(3, 1), 15);
+//! assert_eq!(emulator.pixel(2, 1), 0);
+//! ```
+
+use crate::instruction::consts::{NUM_PIXEL_COLS, NUM_PIXEL_ROWS};
+use crate::instruction::{
+    argument_len, ColumnRemap, ComLayout, ComScanDirection, Command, DisplayMode, IncrementAxis,
+    NibbleRemap,
+};
+use crate::testing::{Event, PinId};
+use std::vec;
+use std::vec::Vec;
+
+/// The largest value of a gray scale table entry, in DCLKs.
+const GRAY_SCALE_MAX: u8 = 180;
+
+/// The factory default linear gray scale table.
+const DEFAULT_GRAY_SCALE_TABLE: [u8; 16] = {
+    let mut table = [0u8; 16];
+    let mut i = 0;
+    while i < 16 {
+        table[i] = (i as u8) * (GRAY_SCALE_MAX / 15);
+        i += 1;
+    }
+    table
+};
+
+/// Software model of an SSD1322 controller driving a panel.
+pub struct Emulator {
+    /// Width of the panel in pixels.
+    width: u16,
+    /// Height of the panel in pixels.
+    height: u16,
+    /// How the panel's rows are wired to the COM lines.
+    wiring: ComLayout,
+    /// Display RAM, one gray level per segment line, row by row.
+    ram: Vec<u8>,
+    /// Column address window, inclusive.
+    columns: (u8, u8),
+    /// Row address window, inclusive.
+    rows: (u8, u8),
+    /// Current column address.
+    column: u8,
+    /// Current row address.
+    row: u8,
+    /// Which of the two bytes at the current column address is written next.
+    high_byte: bool,
+    increment_axis: IncrementAxis,
+    column_remap: ColumnRemap,
+    nibble_remap: NibbleRemap,
+    com_scan_direction: ComScanDirection,
+    com_layout: ComLayout,
+    start_line: u8,
+    display_offset: u8,
+    /// Number of active COM lines (the MUX ratio setting plus one).
+    mux: u8,
+    partial_display: Option<(u8, u8)>,
+    display_mode: DisplayMode,
+    sleeping: bool,
+    command_lock: bool,
+    contrast_current: u8,
+    master_contrast: u8,
+    gray_scale_table: [u8; 16],
+    /// Whether VCC is switched on, following the power on pin.
+    vcc: bool,
+    /// Whether data bytes are currently written to the display RAM.
+    writing_ram: bool,
+    /// A command byte still waiting for its arguments.
+    pending: Option<(u8, Vec<u8>)>,
+}
+
+impl Emulator {
+    /// Creates an emulator for a panel `width` pixels wide and `height` pixels tall, wired with
+    /// dual COM lines like most 256x64 modules. The controller starts in its reset state.
+    ///
+    /// # Panics
+    ///
+    /// If the panel is larger than 480x128 pixels.
+    pub fn new(width: u16, height: u16) -> Self {
+        assert!(width <= NUM_PIXEL_COLS && height <= NUM_PIXEL_ROWS as u16);
+        let ram = vec![0; NUM_PIXEL_COLS as usize * NUM_PIXEL_ROWS as usize];
+        Self::reset_state(width, height, ComLayout::DualProgressive, true, ram)
+    }
+
+    /// Creates an emulator with all controller registers at their reset values.
+    fn reset_state(width: u16, height: u16, wiring: ComLayout, vcc: bool, ram: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            wiring,
+            ram,
+            columns: (0, NUM_PIXEL_COLS as u8 / 4 - 1),
+            rows: (0, NUM_PIXEL_ROWS - 1),
+            column: 0,
+            row: 0,
+            high_byte: true,
+            increment_axis: IncrementAxis::Horizontal,
+            column_remap: ColumnRemap::Forward,
+            nibble_remap: NibbleRemap::Reverse,
+            com_scan_direction: ComScanDirection::RowZeroFirst,
+            com_layout: ComLayout::Progressive,
+            start_line: 0,
+            display_offset: 0,
+            mux: NUM_PIXEL_ROWS,
+            partial_display: None,
+            display_mode: DisplayMode::Normal,
+            sleeping: true,
+            command_lock: false,
+            contrast_current: 0x7F,
+            master_contrast: 0x0F,
+            gray_scale_table: DEFAULT_GRAY_SCALE_TABLE,
+            vcc,
+            writing_ram: false,
+            pending: None,
+        }
+    }
+
+    /// Sets how the panel's rows are wired to the controller's COM lines. Driving the panel with
+    /// a different `ComLayout` in `SetRemapping` scrambles the rows, as it would on hardware.
+    pub fn with_wiring(mut self, wiring: ComLayout) -> Self {
+        self.wiring = wiring;
+        self
+    }
+
+    /// Returns the controller registers to their reset values. The display RAM is unchanged.
+    pub fn reset(&mut self) {
+        let ram = core::mem::take(&mut self.ram);
+        *self = Self::reset_state(self.width, self.height, self.wiring, self.vcc, ram);
+    }
+
+    /// Feeds events recorded by the mocks in the `testing` module into the emulator. Writes are
+    /// processed as command or data bytes, and the reset and power on pins are followed.
+    pub fn feed(&mut self, events: &[Event]) {
+        for event in events {
+            match event {
+                Event::Write { dc, bytes } => self.write(*dc, bytes),
+                Event::Pin {
+                    pin: PinId::Reset,
+                    high: false,
+                } => self.reset(),
+                Event::Pin {
+                    pin: PinId::Power,
+                    high,
+                } => self.vcc = *high,
+                _ => (),
+            }
+        }
+    }
+
+    /// Processes bytes sent to the controller. `dc` is the state of the data/command pin: `false`
+    /// for command bytes and `true` for data bytes.
+    pub fn write(&mut self, dc: bool, bytes: &[u8]) {
+        for &byte in bytes {
+            if dc {
+                self.write_data(byte);
+            } else {
+                self.write_command(byte);
+            }
+        }
+    }
+
+    fn write_command(&mut self, cmd: u8) {
+        // An incomplete previous command is discarded
+        self.pending = None;
+        self.writing_ram = false;
+        if self.command_lock && cmd != 0xFD {
+            return;
+        }
+        match cmd {
+            0x5C => self.writing_ram = true,
+            0xB8 => self.pending = Some((cmd, Vec::new())),
+            _ => match argument_len(cmd) {
+                Some(0) => self.execute(cmd, &[]),
+                Some(_) => self.pending = Some((cmd, Vec::new())),
+                None => (),
+            },
+        }
+    }
+
+    fn write_data(&mut self, byte: u8) {
+        if let Some((cmd, mut args)) = self.pending.take() {
+            args.push(byte);
+            let len = match cmd {
+                0xB8 => 15,
+                _ => argument_len(cmd).unwrap_or(0),
+            };
+            if args.len() == len {
+                self.execute(cmd, &args);
+            } else {
+                self.pending = Some((cmd, args));
+            }
+        } else if self.writing_ram {
+            self.write_ram(byte);
+        }
+    }
+
+    fn execute(&mut self, cmd: u8, args: &[u8]) {
+        if cmd == 0xB8 {
+            self.gray_scale_table[1..].copy_from_slice(args);
+            return;
+        }
+        // Commands with invalid arguments are ignored
+        let Ok(command) = Command::decode(cmd, args) else {
+            return;
+        };
+        match command {
+            Command::SetColumnAddress(start, end) => {
+                self.columns = (start, end);
+                self.column = start;
+                self.high_byte = true;
+            }
+            Command::SetRowAddress(start, end) => {
+                self.rows = (start, end);
+                self.row = start;
+                self.high_byte = true;
+            }
+            Command::SetRemapping(
+                increment_axis,
+                column_remap,
+                nibble_remap,
+                com_scan_direction,
+                com_layout,
+            ) => {
+                self.increment_axis = increment_axis;
+                self.column_remap = column_remap;
+                self.nibble_remap = nibble_remap;
+                self.com_scan_direction = com_scan_direction;
+                self.com_layout = com_layout;
+            }
+            Command::SetStartLine(line) => self.start_line = line,
+            Command::SetDisplayOffset(offset) => self.display_offset = offset,
+            Command::SetDisplayMode(mode) => self.display_mode = mode,
+            Command::EnablePartialDisplay(start, end) => self.partial_display = Some((start, end)),
+            Command::DisablePartialDisplay => self.partial_display = None,
+            Command::SetSleepMode(sleep) => self.sleeping = sleep,
+            Command::SetDefaultGrayScaleTable => self.gray_scale_table = DEFAULT_GRAY_SCALE_TABLE,
+            Command::SetContrastCurrent(current) => self.contrast_current = current,
+            Command::SetMasterContrast(contrast) => self.master_contrast = contrast,
+            Command::SetMuxRatio(ratio) => self.mux = (ratio + 1).min(NUM_PIXEL_ROWS),
+            Command::SetCommandLock(lock) => self.command_lock = lock,
+            _ => (),
+        }
+    }
+
+    fn write_ram(&mut self, byte: u8) {
+        // Each column address holds two bytes, which hold four pixels, one per nibble
+        let first_nibble = if self.high_byte { 0 } else { 2 };
+        for (i, level) in [byte >> 4, byte & 0x0F].into_iter().enumerate() {
+            let nibble = first_nibble + i;
+            let pixel = match self.nibble_remap {
+                NibbleRemap::Forward => nibble,
+                NibbleRemap::Reverse => 3 - nibble,
+            };
+            let segment = self.column as usize * 4 + pixel;
+            let segment = match self.column_remap {
+                ColumnRemap::Forward => segment,
+                ColumnRemap::Reverse => NUM_PIXEL_COLS as usize - 1 - segment,
+            };
+            if segment < NUM_PIXEL_COLS as usize && self.row < NUM_PIXEL_ROWS {
+                self.ram[self.row as usize * NUM_PIXEL_COLS as usize + segment] = level;
+            }
+        }
+
+        self.high_byte = !self.high_byte;
+        if self.high_byte {
+            self.advance_address();
+        }
+    }
+
+    fn advance_address(&mut self) {
+        let (column_start, column_end) = self.columns;
+        let (row_start, row_end) = self.rows;
+        match self.increment_axis {
+            IncrementAxis::Horizontal => {
+                self.column = self.column.wrapping_add(1);
+                if self.column > column_end {
+                    self.column = column_start;
+                    self.row = self.row.wrapping_add(1);
+                    if self.row > row_end {
+                        self.row = row_start;
+                    }
+                }
+            }
+            IncrementAxis::Vertical => {
+                self.row = self.row.wrapping_add(1);
+                if self.row > row_end {
+                    self.row = row_start;
+                    self.column = self.column.wrapping_add(1);
+                    if self.column > column_end {
+                        self.column = column_start;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the gray level (0-15) stored in display RAM for segment line `segment` (0-479) of
+    /// row address `row` (0-127).
+    pub fn ram(&self, segment: u16, row: u8) -> u8 {
+        self.ram[row as usize * NUM_PIXEL_COLS as usize + segment as usize]
+    }
+
+    /// Returns the gray level (0-15) the panel shows at pixel (`x`, `y`).
+    ///
+    /// Pixels are dark while the panel is in sleep mode, VCC is off, or the pixel's row is not
+    /// active because of the MUX ratio or partial display settings.
+    ///
+    /// # Panics
+    ///
+    /// If the pixel is outside of the panel.
+    pub fn pixel(&self, x: u16, y: u16) -> u8 {
+        assert!(x < self.width && y < self.height);
+        if self.sleeping || !self.vcc {
+            return 0;
+        }
+        match self.display_mode {
+            DisplayMode::BlankDark => return 0,
+            DisplayMode::BlankBright => return 15,
+            _ => (),
+        }
+
+        // The panel's rows are wired to the COM lines from the bottom up, so that the image is
+        // upright when the controller scans from `COM[N-1]` to `COM0`
+        let com = com_for_row(self.wiring, (self.height - 1 - y) as u8);
+        let com = row_for_com(self.com_layout, com);
+        let com = ((com as u16 + self.display_offset as u16) % NUM_PIXEL_ROWS as u16) as u8;
+        if com >= self.mux {
+            return 0;
+        }
+
+        // The controller scans its rows from `COM0` to `COM[N-1]`, or from `COM[N-1]` to `COM0`,
+        // where N is the MUX ratio
+        let scan_row = match self.com_scan_direction {
+            ComScanDirection::RowZeroFirst => com,
+            ComScanDirection::RowZeroLast => self.mux - 1 - com,
+        };
+        if let Some((start, end)) = self.partial_display {
+            if scan_row < start || scan_row > end {
+                return 0;
+            }
+        }
+        let row = ((scan_row as u16 + self.start_line as u16) % NUM_PIXEL_ROWS as u16) as u8;
+        let segment = (NUM_PIXEL_COLS - self.width) / 2 + x;
+        let level = self.ram(segment, row);
+        match self.display_mode {
+            DisplayMode::Inverse => 15 - level,
+            _ => level,
+        }
+    }
+
+    /// Renders the gray levels (0-15) shown on the whole panel, row by row.
+    pub fn render(&self) -> Vec<u8> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.pixel(x, y))
+            .collect()
+    }
+
+    /// Renders the relative luminance (0-255) shown on the whole panel, row by row. This applies
+    /// the gray scale table, the contrast current and the master contrast to the gray levels
+    /// returned by `render`.
+    pub fn render_luminance(&self) -> Vec<u8> {
+        let scale = self.contrast_current as u32 * (self.master_contrast as u32 + 1);
+        self.render()
+            .into_iter()
+            .map(|level| {
+                let pulse = self.gray_scale_table[level as usize].min(GRAY_SCALE_MAX) as u32;
+                (pulse * scale / (GRAY_SCALE_MAX as u32 * 16)) as u8
+            })
+            .collect()
+    }
+
+    /// Width of the panel in pixels.
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Height of the panel in pixels.
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Whether the controller is in sleep mode.
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// The current display mode.
+    pub fn display_mode(&self) -> DisplayMode {
+        self.display_mode
+    }
+
+    /// The current display start line.
+    pub fn start_line(&self) -> u8 {
+        self.start_line
+    }
+
+    /// The current display offset.
+    pub fn display_offset(&self) -> u8 {
+        self.display_offset
+    }
+
+    /// The number of active COM lines set by the MUX ratio.
+    pub fn mux(&self) -> u8 {
+        self.mux
+    }
+
+    /// The inclusive range of rows enabled by partial display mode, if enabled.
+    pub fn partial_display(&self) -> Option<(u8, u8)> {
+        self.partial_display
+    }
+
+    /// The current contrast current setting.
+    pub fn contrast_current(&self) -> u8 {
+        self.contrast_current
+    }
+
+    /// The current master contrast setting.
+    pub fn master_contrast(&self) -> u8 {
+        self.master_contrast
+    }
+
+    /// The current gray scale table, in DCLKs for each gray level.
+    pub fn gray_scale_table(&self) -> [u8; 16] {
+        self.gray_scale_table
+    }
+}
+
+/// The COM line that display row `row` is connected to for the given layout.
+fn com_for_row(layout: ComLayout, row: u8) -> u8 {
+    match layout {
+        ComLayout::Progressive | ComLayout::DualProgressive => row,
+        ComLayout::Interlaced if row % 2 == 0 => row / 2,
+        ComLayout::Interlaced => NUM_PIXEL_ROWS / 2 + row / 2,
+    }
+}
+
+/// The display row that COM line `com` is connected to for the given layout.
+fn row_for_com(layout: ComLayout, com: u8) -> u8 {
+    match layout {
+        ComLayout::Progressive => com,
+        ComLayout::DualProgressive => com % (NUM_PIXEL_ROWS / 2),
+        ComLayout::Interlaced if com < NUM_PIXEL_ROWS / 2 => com * 2,
+        ComLayout::Interlaced => (com - NUM_PIXEL_ROWS / 2) * 2 + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::Command;
+
+    fn send(emulator: &mut Emulator, command: Command) {
+        let command = command.prepare().unwrap();
+        emulator.write(false, &[command.cmd]);
+        if !command.args().is_empty() {
+            emulator.write(true, command.args());
+        }
+    }
+
+    // A 256x64 panel switched on with `scan` and `column`, and a MUX ratio of 64
+    fn panel(column: ColumnRemap, scan: ComScanDirection) -> Emulator {
+        let mut emulator = Emulator::new(256, 64);
+        send(
+            &mut emulator,
+            Command::SetRemapping(IncrementAxis::Horizontal, column, NibbleRemap::Forward, scan, ComLayout::DualProgressive),
+        );
+        send(&mut emulator, Command::SetMuxRatio(63));
+        send(&mut emulator, Command::SetSleepMode(false));
+        emulator
+    }
+
+    // Fills `rows` of the display RAM under the panel with level 15
+    fn light(emulator: &mut Emulator, rows: (u8, u8)) {
+        send(emulator, Command::SetColumnAddress(28, 91));
+        send(emulator, Command::SetRowAddress(rows.0, rows.1));
+        send(emulator, Command::WriteRam);
+        let len = (rows.1 - rows.0 + 1) as usize * 128;
+        emulator.write(true, &vec![0xFF; len]);
+    }
+
+    fn lit_rows(emulator: &Emulator) -> Vec<u16> {
+        (0..64).filter(|&y| emulator.pixel(0, y) != 0).collect()
+    }
+
+    #[test]
+    fn row_zero_last_shows_ram_row_zero_at_the_top() {
+        let mut emulator = panel(ColumnRemap::Forward, ComScanDirection::RowZeroLast);
+        light(&mut emulator, (0, 0));
+        light(&mut emulator, (63, 63));
+        assert_eq!(lit_rows(&emulator), [0, 63]);
+        light(&mut emulator, (64, 127));
+        assert_eq!(lit_rows(&emulator), [0, 63]);
+    }
+
+    #[test]
+    fn row_zero_first_flips_the_image_vertically() {
+        let mut emulator = panel(ColumnRemap::Forward, ComScanDirection::RowZeroFirst);
+        light(&mut emulator, (0, 0));
+        assert_eq!(lit_rows(&emulator), [63]);
+    }
+
+    #[test]
+    fn column_remap_mirrors_the_image_horizontally() {
+        for (column, lit) in [(ColumnRemap::Forward, 0..4), (ColumnRemap::Reverse, 252..256)] {
+            let mut emulator = panel(column, ComScanDirection::RowZeroLast);
+            send(&mut emulator, Command::SetColumnAddress(28, 28));
+            send(&mut emulator, Command::SetRowAddress(0, 0));
+            send(&mut emulator, Command::WriteRam);
+            emulator.write(true, &[0xFF, 0xFF]);
+            let lit_columns: Vec<u16> = (0..256).filter(|&x| emulator.pixel(x, 0) != 0).collect();
+            assert_eq!(lit_columns, lit.collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn display_offset_moves_the_com_lines() {
+        let mut emulator = panel(ColumnRemap::Forward, ComScanDirection::RowZeroLast);
+        light(&mut emulator, (0, 0));
+        send(&mut emulator, Command::SetDisplayOffset(1));
+        assert_eq!(lit_rows(&emulator), [1]);
+        assert_eq!(emulator.pixel(0, 0), 0);
+    }
+
+    #[test]
+    fn start_line_selects_the_first_ram_row_shown() {
+        let mut emulator = panel(ColumnRemap::Forward, ComScanDirection::RowZeroLast);
+        light(&mut emulator, (1, 1));
+        send(&mut emulator, Command::SetStartLine(1));
+        assert_eq!(lit_rows(&emulator), [0]);
+
+        // Rows wrap around the end of the display RAM
+        light(&mut emulator, (0, 0));
+        send(&mut emulator, Command::SetStartLine(65));
+        assert_eq!(lit_rows(&emulator), [63]);
+    }
+
+    #[test]
+    fn mux_ratio_limits_the_com_lines_scanned() {
+        // Scanning COM[N-1] to COM0 shows row 0 of the display RAM on COM[N-1], so lowering the
+        // MUX ratio moves the image
+        let mut emulator = panel(ColumnRemap::Forward, ComScanDirection::RowZeroLast);
+        light(&mut emulator, (0, 0));
+        send(&mut emulator, Command::SetMuxRatio(15));
+        assert_eq!(lit_rows(&emulator), [48]);
+        light(&mut emulator, (0, 127));
+        assert_eq!(lit_rows(&emulator), (48..64).collect::<Vec<_>>());
+
+        // Scanning COM0 to COM[N-1] shows row 0 of the display RAM on COM0 whatever the ratio
+        let mut emulator = panel(ColumnRemap::Forward, ComScanDirection::RowZeroFirst);
+        light(&mut emulator, (0, 0));
+        send(&mut emulator, Command::SetMuxRatio(15));
+        assert_eq!(lit_rows(&emulator), [63]);
+        assert_eq!(emulator.mux(), 16);
+    }
+
+    #[test]
+    fn partial_display_turns_off_rows_outside_the_range() {
+        let mut emulator = panel(ColumnRemap::Forward, ComScanDirection::RowZeroLast);
+        light(&mut emulator, (0, 127));
+        send(&mut emulator, Command::EnablePartialDisplay(0, 15));
+        assert_eq!(emulator.partial_display(), Some((0, 15)));
+        assert_eq!(lit_rows(&emulator), (0..16).collect::<Vec<_>>());
+
+        send(&mut emulator, Command::EnablePartialDisplay(16, 63));
+        assert_eq!(lit_rows(&emulator), (16..64).collect::<Vec<_>>());
+
+        send(&mut emulator, Command::DisablePartialDisplay);
+        assert_eq!(lit_rows(&emulator), (0..64).collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "frame")]
+    mod driver {
+        use super::*;
+        use crate::testing::{block_on, Recorder};
+        use crate::instruction::CommandError;
+        use crate::{calculate_buffer_size, Config, Error, Frame, Orientation};
+        use embedded_graphics_core::pixelcolor::Gray4;
+
+        type PanelFrame = Frame<{ calculate_buffer_size(256, 64) }>;
+
+        // Pixels of an image that looks different after any flip or rotation: (x, y, level)
+        const MARKS: [(u16, u16, u8); 5] = [(0, 0, 1), (5, 0, 2), (255, 0, 3), (0, 63, 4), (10, 20, 5)];
+
+        fn marked_frame() -> PanelFrame {
+            let mut frame = PanelFrame::default();
+            for (x, y, level) in MARKS {
+                frame.set_pixel(x, y, Gray4::new(level));
+            }
+            frame
+        }
+
+        // Expected rendering of a frame on an upright panel
+        fn image(frame: &PanelFrame) -> Vec<u8> {
+            (0..64)
+                .flat_map(|y| (0..256).map(move |x| (x, y)))
+                .map(|(x, y)| frame.level(x, y).unwrap())
+                .collect()
+        }
+
+        // Flushes `frame` through a driver set up with `config` and renders what a panel wired
+        // with `wiring` shows
+        fn render(config: Config, wiring: ComLayout, frame: &PanelFrame) -> Vec<u8> {
+            let recorder = Recorder::new();
+            let mut display = recorder.display(config);
+            block_on(async {
+                display.init_default(&mut recorder.delay()).await?;
+                display.flush_frame(frame).await
+            })
+            .unwrap();
+            let mut emulator = Emulator::new(256, 64).with_wiring(wiring);
+            emulator.feed(&recorder.events());
+            emulator.render()
+        }
+
+        #[test]
+        fn orientations_and_nibble_orders_render_the_frame() {
+            for orientation in [
+                Orientation::Standard,
+                Orientation::Inverted,
+                Orientation::MirrorHorizontal,
+                Orientation::MirrorVertical,
+            ] {
+                let mut expected = vec![0; 256 * 64];
+                for (x, y, level) in MARKS {
+                    let (x, y) = match orientation {
+                        Orientation::Standard => (x, y),
+                        Orientation::Inverted => (255 - x, 63 - y),
+                        Orientation::MirrorHorizontal => (255 - x, y),
+                        Orientation::MirrorVertical => (x, 63 - y),
+                    };
+                    expected[y as usize * 256 + x as usize] = level;
+                }
+
+                for nibble_order in [NibbleRemap::Forward, NibbleRemap::Reverse] {
+                    let mut frame = marked_frame();
+                    frame.set_nibble_order(nibble_order);
+                    let config = Config { orientation, nibble_order, ..Config::default() };
+                    assert!(render(config, ComLayout::DualProgressive, &frame) == expected, "{orientation:?}, {nibble_order:?}");
+                }
+            }
+        }
+
+        #[test]
+        fn interlaced_com_layout_matches_interlaced_wiring() {
+            // Every row differs from its neighbours, so swapped rows show
+            let mut frame = marked_frame();
+            for y in 0..64 {
+                frame.set_pixel(100, y, Gray4::new(y as u8 % 15 + 1));
+            }
+            let expected = image(&frame);
+
+            let config = Config { com_layout: ComLayout::Interlaced, ..Config::default() };
+            assert!(render(config, ComLayout::Interlaced, &frame) == expected);
+
+            // The default layout scrambles the rows of an interlaced panel
+            assert!(render(Config::default(), ComLayout::Interlaced, &frame) != expected);
+            assert!(render(Config::default(), ComLayout::DualProgressive, &frame) == expected);
+        }
+
+        #[test]
+        fn active_rows_light_the_band_with_the_image_in_place() {
+            // Every row differs from the others, so moved rows show
+            let mut frame = PanelFrame::default();
+            for y in 0..64 {
+                frame.set_pixel(0, y, Gray4::new(y as u8 % 16));
+                frame.set_pixel(1, y, Gray4::new(y as u8 / 16 + 1));
+            }
+            for orientation in [
+                Orientation::Standard,
+                Orientation::Inverted,
+                Orientation::MirrorHorizontal,
+                Orientation::MirrorVertical,
+            ] {
+                let flipped = matches!(orientation, Orientation::Inverted | Orientation::MirrorVertical);
+                for display_offset in [0, 5] {
+                    let config = || Config { orientation, display_offset, ..Config::default() };
+                    let whole = render(config(), ComLayout::DualProgressive, &frame);
+                    for band in [0..=15, 48..=63, 20..=30, 0..=63, 60..=61, 2..=6, 0..=3] {
+                        let recorder = Recorder::new();
+                        let mut display = recorder.display(config());
+                        block_on(async {
+                            display.init_default(&mut recorder.delay()).await?;
+                            display.flush_frame(&frame).await
+                        })
+                        .unwrap();
+                        let context = (orientation, display_offset, band.clone());
+
+                        // Bands the display offset leaves dark anyway are rejected
+                        let result = block_on(display.set_active_rows(band.clone()));
+                        let dark = if flipped { band.start() + display_offset >= 64 } else { *band.end() < display_offset };
+                        if dark {
+                            assert!(matches!(result, Err(Error::CommandError(CommandError::OutOfRange))), "{context:?}");
+                            continue;
+                        }
+                        result.unwrap();
+                        let mut emulator = Emulator::new(256, 64);
+                        emulator.feed(&recorder.events());
+
+                        // Only the band is lit, as it was with the whole panel lit, and only the
+                        // COM lines up to the band are scanned
+                        let expected: Vec<u8> = whole
+                            .chunks(256)
+                            .enumerate()
+                            .flat_map(|(y, row)| {
+                                let image_row = if flipped { 63 - y as u8 } else { y as u8 };
+                                let lit = band.contains(&image_row);
+                                row.iter().map(move |&level| if lit { level } else { 0 })
+                            })
+                            .collect();
+                        assert!(emulator.render() == expected, "{context:?}");
+                        let reach = if flipped { band.end() + 1 } else { 64 - band.start() } + display_offset;
+                        assert_eq!(emulator.mux(), reach.clamp(16, 64), "{context:?}");
+
+                        block_on(display.set_all_rows_active()).unwrap();
+                        let mut emulator = Emulator::new(256, 64);
+                        emulator.feed(&recorder.events());
+                        assert!(emulator.render() == whole, "{context:?}");
+                    }
+                }
+            }
+        }
+    }
+}
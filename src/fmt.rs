@@ -0,0 +1,39 @@
+//! Logging macros which forward to `defmt` or `log`, depending on the enabled features, and
+//! compile to nothing otherwise. If both features are enabled, messages only go to `defmt`, so that
+//! they aren't logged twice. Format strings must only use `{}` and `{:?}`, which both crates
+//! understand.
+#![macro_use]
+#![allow(unused_macros)]
+
+macro_rules! trace {
+    ($s:literal $(, $x:expr)* $(,)?) => {{
+        #[cfg(feature = "defmt")]
+        ::defmt::trace!($s $(, $x)*);
+        #[cfg(all(feature = "log", not(feature = "defmt")))]
+        ::log::trace!($s $(, $x)*);
+        #[cfg(not(any(feature = "defmt", feature = "log")))]
+        let _ = ($(&$x),*);
+    }};
+}
+
+macro_rules! debug {
+    ($s:literal $(, $x:expr)* $(,)?) => {{
+        #[cfg(feature = "defmt")]
+        ::defmt::debug!($s $(, $x)*);
+        #[cfg(all(feature = "log", not(feature = "defmt")))]
+        ::log::debug!($s $(, $x)*);
+        #[cfg(not(any(feature = "defmt", feature = "log")))]
+        let _ = ($(&$x),*);
+    }};
+}
+
+macro_rules! info {
+    ($s:literal $(, $x:expr)* $(,)?) => {{
+        #[cfg(feature = "defmt")]
+        ::defmt::info!($s $(, $x)*);
+        #[cfg(all(feature = "log", not(feature = "defmt")))]
+        ::log::info!($s $(, $x)*);
+        #[cfg(not(any(feature = "defmt", feature = "log")))]
+        let _ = ($(&$x),*);
+    }};
+}
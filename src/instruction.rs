@@ -3,12 +3,17 @@
 use crate::instruction::consts::*;
 
 /// Errors that can occur in commands.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum CommandError{
     /// An argument to the command was outside of the valid range.
     OutOfRange,
     /// The gray scale table provided was not the correct length.
     BadTableLength,
+    /// The command byte being decoded is not one that `Command` can represent.
+    UnknownCommand,
+    /// The number of argument bytes given does not match the command byte being decoded.
+    BadArgumentLength,
 }
 
 pub mod consts {
@@ -44,6 +49,7 @@ pub mod consts {
 /// - `data`: An array containing up to two data bytes to be sent with the command.
 /// - `len`: The length of the data to be sent (should be 0, 1, or 2).
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct CommandData {
     /// The command byte to be sent to the display.
     pub cmd: u8,
@@ -53,10 +59,18 @@ pub struct CommandData {
     pub len: usize,
 }
 
+impl CommandData {
+    /// The data bytes that should be sent after the command byte.
+    pub fn args(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
 /// The address increment orientation when writing image data. This configures how the SSD1322 will
 /// auto-increment the row and column addresses when image data is written using the
 /// `WriteImageData` command.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum IncrementAxis {
     /// The column address will increment as image data is written, writing pairs of bytes
     /// (horizontal groups of 4 pixels) from left to right in the range set by `SetColumnAddress`
@@ -70,7 +84,8 @@ pub enum IncrementAxis {
 
 /// Setting of column address remapping. This controls the direction of mapping display RAM column
 /// addresses onto groups of pixel column driver lines.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ColumnRemap {
     /// Column addresses 0->119 map to pixel columns 0,1,2,3->476,477,478,479.
     Forward,
@@ -83,7 +98,8 @@ pub enum ColumnRemap {
 /// Setting of data nibble remapping. This controls how the SSD1322 will interpret the nibble-wise
 /// endianness of each 2-byte word, changing the order in which each group of 4 pixels is mapped
 /// onto the 4 nibbles stored at the corresponding display RAM column address.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum NibbleRemap {
     /// The 2-byte sequence at each column address 0xABCD maps (in L->R order) to pixels 3,2,1,0.
     Reverse,
@@ -94,7 +110,8 @@ pub enum NibbleRemap {
 /// Setting of the COM line scanning of rows. This controls the order in which COM lines are
 /// scanned, leaving the order in which display RAM row addresses are scanned unchanged. Toggling
 /// this setting will thus flip the displayed image vertically.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ComScanDirection {
     /// COM lines scan row addresses top to bottom, so that row address 0 is the first row of the
     /// display.
@@ -108,7 +125,8 @@ pub enum ComScanDirection {
 /// display module itself wires the OLED matrix to the driver chip, and changing it to anything
 /// other than the correct setting for your module will yield a corrupted image. See the display
 /// module datasheet for the correct value to use.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ComLayout {
     /// COM lines are connected to display rows in a progressive arrangement, so that COM lines
     /// 0->127 map to display rows 0->127.
@@ -126,7 +144,8 @@ pub enum ComLayout {
 
 /// Setting of the display mode. The display mode controls whether the display is blanked, and
 /// whether the pixel intensities are rendered normal or inverted.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DisplayMode {
     /// The display is blanked with all pixels turned OFF (to grayscale level 0).
     BlankDark,
@@ -141,7 +160,8 @@ pub enum DisplayMode {
 
 /// Setting of the Function Selection. This double byte command is used to enable or disable 
 /// the internal VDD regulator.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FunctionSelection {
     /// Disable Internal VDD, use external VDD.
     ExternalVDD,
@@ -149,10 +169,56 @@ pub enum FunctionSelection {
     InternalVDD
 }
 
+/// Setting of the VSL (segment low voltage) source, part of display enhancement A.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum VslSource {
+    /// Use an external VSL. The display module must provide the external VSL circuitry.
+    External,
+    /// Use the internal VSL (Default).
+    Internal,
+}
+
+/// Setting of the low gray scale display quality, part of display enhancement A.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LowGrayScaleQuality {
+    /// Normal low gray scale display quality (Default).
+    Normal,
+    /// Enhanced low gray scale display quality.
+    Enhanced,
+}
+
+/// Setting of display enhancement B. The datasheet documents the enhanced setting as reserved,
+/// but many display module datasheets recommend it to enhance the driving scheme capability.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DisplayEnhancementB {
+    /// Normal driving scheme (Default).
+    Normal,
+    /// Enhanced driving scheme capability.
+    Enhanced,
+}
+
+/// Setting of a single GPIO pin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GpioMode {
+    /// The pin is high impedance with its input disabled.
+    InputDisabled,
+    /// The pin is high impedance with its input enabled.
+    InputEnabled,
+    /// The pin is driven low (Default).
+    OutputLow,
+    /// The pin is driven high.
+    OutputHigh,
+}
+
 /// Enumerates most of the valid Instructions that can be sent to the SSD1322 along with their
 /// parameter values. Commands which accept an array of similar "arguments" as a slice are encoded
 /// by `BufCommand` instead to avoid lifetime parameters on this enum.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Command  {
     /// Enable the gray scale gamma table (see `BufCommand::SetGrayScaleTable`).
     EnableGrayScaleTable,
@@ -217,8 +283,15 @@ pub enum Command  {
     /// rate of the display (the exact rate depends on the MUX ratio and some other things).
     SetClockFoscDivset(u8, u8),
     /// Enable or disable display enhancements "external VSL" and "Enhanced low GS display
-    /// quality".
+    /// quality". This encodes the same command as `SetDisplayEnhancementA`, which should be
+    /// preferred.
     SetDisplayEnhancements(bool, bool),
+    /// Set display enhancement A, selecting the VSL source and the low gray scale display quality.
+    SetDisplayEnhancementA(VslSource, LowGrayScaleQuality),
+    /// Set display enhancement B. See enum for details.
+    SetDisplayEnhancementB(DisplayEnhancementB),
+    /// Set the mode of the GPIO0 and GPIO1 pins respectively.
+    SetGpio(GpioMode, GpioMode),
     /// Set the second pre-charge period. Range 0-15 DCLKs.
     SetSecondPrechargePeriod(u8),
     /// Set the gray scale gamma table to the factory default.
@@ -370,6 +443,34 @@ impl Command  {
                 };
                 ok_command!(arg_buf, 0xB4, [vsl, gs])
             }
+            Command::SetDisplayEnhancementA(vsl_source, low_gs_quality) => {
+                let vsl = match vsl_source {
+                    VslSource::External => 0xA0,
+                    VslSource::Internal => 0xA2,
+                };
+                let gs = match low_gs_quality {
+                    LowGrayScaleQuality::Enhanced => 0xFD,
+                    LowGrayScaleQuality::Normal => 0xB5,
+                };
+                ok_command!(arg_buf, 0xB4, [vsl, gs])
+            }
+            Command::SetDisplayEnhancementB(enhancement) => {
+                let e = match enhancement {
+                    DisplayEnhancementB::Normal => 0xA2,
+                    DisplayEnhancementB::Enhanced => 0x82,
+                };
+                // The second byte is fixed by the datasheet
+                ok_command!(arg_buf, 0xD1, [e, 0x20])
+            }
+            Command::SetGpio(gpio0, gpio1) => {
+                let mode = |gpio| match gpio {
+                    GpioMode::InputDisabled => 0b00,
+                    GpioMode::InputEnabled => 0b01,
+                    GpioMode::OutputLow => 0b10,
+                    GpioMode::OutputHigh => 0b11,
+                };
+                ok_command!(arg_buf, 0xB5, [mode(gpio1) << 2 | mode(gpio0)])
+            }
             Command::SetSecondPrechargePeriod(period) => match period {
                 0..=15 => ok_command!(arg_buf, 0xB6, [period]),
                 _ => Err(CommandError::OutOfRange),
@@ -409,4 +510,319 @@ impl Command  {
             len,
         })
     }
-}
\ No newline at end of file
+    /// Decode a command byte and its argument bytes back into the `Command` that encodes them.
+    ///
+    /// This is the inverse of `prepare`, and is useful for analysing captured SPI traffic or
+    /// checking hand-written initialisation tables against the datasheet. Every decoded command
+    /// is range checked exactly as `prepare` would check it.
+    ///
+    /// Note that `SetPhaseLengths` only stores the first phase length to a resolution of 2
+    /// DCLKs, so an even first phase length will decode as the odd length below it, and that
+    /// command `0xB4` always decodes as `SetDisplayEnhancementA`.
+    ///
+    /// # Errors
+    ///
+    /// - `CommandError::UnknownCommand`: If `cmd` is not a command byte that `Command` represents.
+    /// - `CommandError::BadArgumentLength`: If `args` has the wrong length for `cmd`.
+    /// - `CommandError::OutOfRange`: If an argument is not valid for `cmd`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ssd1322_rs::instruction::{Command, CommandError};
+    /// assert_eq!(Command::decode(0xC1, &[0x80]), Ok(Command::SetContrastCurrent(0x80)));
+    /// assert_eq!(Command::decode(0xC7, &[0x20]), Err(CommandError::OutOfRange));
+    ///
+    /// let prepared = Command::SetClockFoscDivset(9, 1).prepare()?;
+    /// assert_eq!(Command::decode(prepared.cmd, prepared.args()), Ok(Command::SetClockFoscDivset(9, 1)));
+    /// # Ok::<(), CommandError>(())
+    /// ```
+    pub fn decode(cmd: u8, args: &[u8]) -> Result<Command, CommandError> {
+        let len = argument_len(cmd).ok_or(CommandError::UnknownCommand)?;
+        if args.len() != len {
+            return Err(CommandError::BadArgumentLength);
+        }
+
+        let command = match (cmd, args) {
+            (0x00, _) => Command::EnableGrayScaleTable,
+            (0x15, &[start, end]) => Command::SetColumnAddress(start, end),
+            (0x5C, _) => Command::WriteRam,
+            (0x5D, _) => Command::ReadRam,
+            (0x75, &[start, end]) => Command::SetRowAddress(start, end),
+            (0xA0, &[remap, dual_com]) => {
+                if remap & 0xC8 != 0 {
+                    return Err(CommandError::OutOfRange);
+                }
+                let increment_axis = match remap & 0x01 {
+                    0x00 => IncrementAxis::Horizontal,
+                    _ => IncrementAxis::Vertical,
+                };
+                let column_remap = match remap & 0x02 {
+                    0x00 => ColumnRemap::Forward,
+                    _ => ColumnRemap::Reverse,
+                };
+                let nibble_remap = match remap & 0x04 {
+                    0x00 => NibbleRemap::Reverse,
+                    _ => NibbleRemap::Forward,
+                };
+                let com_scan_direction = match remap & 0x10 {
+                    0x00 => ComScanDirection::RowZeroFirst,
+                    _ => ComScanDirection::RowZeroLast,
+                };
+                let com_layout = match (remap & 0x20, dual_com) {
+                    (0x00, 0x01) => ComLayout::Progressive,
+                    (0x20, 0x01) => ComLayout::Interlaced,
+                    (0x00, 0x11) => ComLayout::DualProgressive,
+                    _ => return Err(CommandError::OutOfRange),
+                };
+                Command::SetRemapping(
+                    increment_axis,
+                    column_remap,
+                    nibble_remap,
+                    com_scan_direction,
+                    com_layout,
+                )
+            }
+            (0xA1, &[line]) => Command::SetStartLine(line),
+            (0xA2, &[line]) => Command::SetDisplayOffset(line),
+            (0xA4, _) => Command::SetDisplayMode(DisplayMode::BlankDark),
+            (0xA5, _) => Command::SetDisplayMode(DisplayMode::BlankBright),
+            (0xA6, _) => Command::SetDisplayMode(DisplayMode::Normal),
+            (0xA7, _) => Command::SetDisplayMode(DisplayMode::Inverse),
+            (0xA8, &[start, end]) => Command::EnablePartialDisplay(start, end),
+            (0xA9, _) => Command::DisablePartialDisplay,
+            (0xAB, &[mode]) => Command::FunctionSelect(match mode {
+                0 => FunctionSelection::ExternalVDD,
+                1 => FunctionSelection::InternalVDD,
+                _ => return Err(CommandError::OutOfRange),
+            }),
+            (0xAE, _) => Command::SetSleepMode(true),
+            (0xAF, _) => Command::SetSleepMode(false),
+            (0xB1, &[phases]) => Command::SetPhaseLengths(((phases & 0x0F) << 1) + 1, phases >> 4),
+            (0xB3, &[clock]) => Command::SetClockFoscDivset(clock >> 4, clock & 0x0F),
+            (0xB4, &[vsl, gs]) => Command::SetDisplayEnhancementA(
+                match vsl {
+                    0xA0 => VslSource::External,
+                    0xA2 => VslSource::Internal,
+                    _ => return Err(CommandError::OutOfRange),
+                },
+                match gs {
+                    0xFD => LowGrayScaleQuality::Enhanced,
+                    0xB5 => LowGrayScaleQuality::Normal,
+                    _ => return Err(CommandError::OutOfRange),
+                },
+            ),
+            (0xB5, &[gpio]) => {
+                if gpio & 0xF0 != 0 {
+                    return Err(CommandError::OutOfRange);
+                }
+                let mode = |bits| match bits & 0b11 {
+                    0b00 => GpioMode::InputDisabled,
+                    0b01 => GpioMode::InputEnabled,
+                    0b10 => GpioMode::OutputLow,
+                    _ => GpioMode::OutputHigh,
+                };
+                Command::SetGpio(mode(gpio), mode(gpio >> 2))
+            }
+            (0xB6, &[period]) => Command::SetSecondPrechargePeriod(period),
+            (0xB9, _) => Command::SetDefaultGrayScaleTable,
+            (0xBB, &[voltage]) => Command::SetPreChargeVoltage(voltage),
+            (0xBE, &[voltage]) => Command::SetComDeselectVoltage(voltage),
+            (0xC1, &[current]) => Command::SetContrastCurrent(current),
+            (0xC7, &[contrast]) => Command::SetMasterContrast(contrast),
+            (0xCA, &[ratio]) => Command::SetMuxRatio(ratio),
+            (0xD1, &[enhancement, fixed]) => Command::SetDisplayEnhancementB(match (enhancement, fixed) {
+                (0xA2, 0x20) => DisplayEnhancementB::Normal,
+                (0x82, 0x20) => DisplayEnhancementB::Enhanced,
+                _ => return Err(CommandError::OutOfRange),
+            }),
+            (0xFD, &[lock]) => Command::SetCommandLock(match lock {
+                0x16 => true,
+                0x12 => false,
+                _ => return Err(CommandError::OutOfRange),
+            }),
+            _ => return Err(CommandError::UnknownCommand),
+        };
+
+        // Reuse the range checks applied when encoding
+        command.prepare()?;
+        Ok(command)
+    }
+}
+
+/// The number of argument bytes that follow the command byte `cmd`, or `None` if `cmd` is not a
+/// command byte that `Command` represents.
+pub(crate) fn argument_len(cmd: u8) -> Option<usize> {
+    match cmd {
+        0x00 | 0x5C | 0x5D | 0xA4..=0xA7 | 0xA9 | 0xAE | 0xAF | 0xB9 => Some(0),
+        0xA1 | 0xA2 | 0xAB | 0xB1 | 0xB3 | 0xB5 | 0xB6 | 0xBB | 0xBE | 0xC1 | 0xC7 | 0xCA
+        | 0xFD => Some(1),
+        0x15 | 0x75 | 0xA0 | 0xA8 | 0xB4 | 0xD1 => Some(2),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    // One or more values of every `Command` variant, covering every value of each enum argument
+    // and the ends of each numeric range
+    fn every_command() -> Vec<Command> {
+        use ComLayout::*;
+        use ComScanDirection::*;
+        use GpioMode::*;
+
+        let mut commands = Vec::from([
+            Command::EnableGrayScaleTable,
+            Command::SetColumnAddress(0, BUF_COL_MAX),
+            Command::SetColumnAddress(28, 91),
+            Command::WriteRam,
+            Command::ReadRam,
+            Command::SetRowAddress(0, PIXEL_ROW_MAX),
+            Command::SetRowAddress(64, 127),
+            Command::SetStartLine(0),
+            Command::SetStartLine(PIXEL_ROW_MAX),
+            Command::SetDisplayOffset(0),
+            Command::SetDisplayOffset(PIXEL_ROW_MAX),
+            Command::EnablePartialDisplay(0, 15),
+            Command::EnablePartialDisplay(PIXEL_ROW_MAX, PIXEL_ROW_MAX),
+            Command::DisablePartialDisplay,
+            Command::FunctionSelect(FunctionSelection::ExternalVDD),
+            Command::FunctionSelect(FunctionSelection::InternalVDD),
+            Command::SetSleepMode(true),
+            Command::SetSleepMode(false),
+            Command::SetPhaseLengths(5, 3),
+            Command::SetPhaseLengths(31, 15),
+            Command::SetClockFoscDivset(0, 0),
+            Command::SetClockFoscDivset(15, 10),
+            Command::SetDisplayEnhancementB(DisplayEnhancementB::Normal),
+            Command::SetDisplayEnhancementB(DisplayEnhancementB::Enhanced),
+            Command::SetSecondPrechargePeriod(0),
+            Command::SetSecondPrechargePeriod(15),
+            Command::SetDefaultGrayScaleTable,
+            Command::SetPreChargeVoltage(0),
+            Command::SetPreChargeVoltage(31),
+            Command::SetComDeselectVoltage(0),
+            Command::SetComDeselectVoltage(7),
+            Command::SetContrastCurrent(0),
+            Command::SetContrastCurrent(255),
+            Command::SetMasterContrast(0),
+            Command::SetMasterContrast(15),
+            Command::SetMuxRatio(15),
+            Command::SetMuxRatio(63),
+            Command::SetCommandLock(true),
+            Command::SetCommandLock(false),
+        ]);
+        for mode in [DisplayMode::BlankDark, DisplayMode::BlankBright, DisplayMode::Normal, DisplayMode::Inverse] {
+            commands.push(Command::SetDisplayMode(mode));
+        }
+        for vsl in [VslSource::External, VslSource::Internal] {
+            for quality in [LowGrayScaleQuality::Normal, LowGrayScaleQuality::Enhanced] {
+                commands.push(Command::SetDisplayEnhancementA(vsl, quality));
+            }
+        }
+        for gpio0 in [InputDisabled, InputEnabled, OutputLow, OutputHigh] {
+            for gpio1 in [InputDisabled, InputEnabled, OutputLow, OutputHigh] {
+                commands.push(Command::SetGpio(gpio0, gpio1));
+            }
+        }
+        for axis in [IncrementAxis::Horizontal, IncrementAxis::Vertical] {
+            for column in [ColumnRemap::Forward, ColumnRemap::Reverse] {
+                for nibble in [NibbleRemap::Forward, NibbleRemap::Reverse] {
+                    for scan in [RowZeroFirst, RowZeroLast] {
+                        for layout in [Progressive, Interlaced, DualProgressive] {
+                            commands.push(Command::SetRemapping(axis, column, nibble, scan, layout));
+                        }
+                    }
+                }
+            }
+        }
+        commands
+    }
+
+    #[test]
+    fn every_variant_round_trips() {
+        let commands = every_command();
+        let mut variants = Vec::new();
+        for command in commands {
+            let prepared = command.prepare().unwrap();
+            assert_eq!(Command::decode(prepared.cmd, prepared.args()), Ok(command));
+            let variant = core::mem::discriminant(&command);
+            if !variants.contains(&variant) {
+                variants.push(variant);
+            }
+        }
+        // Every variant except `SetDisplayEnhancements`, which is tested below
+        assert_eq!(variants.len(), VARIANTS - 1);
+    }
+
+    // The number of `Command` variants. The match stops compiling when a variant is added, as a
+    // reminder to add it to `every_command` and update the count.
+    const VARIANTS: usize = 27;
+    #[allow(dead_code)]
+    fn variants(command: Command) {
+        match command {
+            Command::EnableGrayScaleTable
+            | Command::SetColumnAddress(..)
+            | Command::WriteRam
+            | Command::ReadRam
+            | Command::SetRowAddress(..)
+            | Command::SetRemapping(..)
+            | Command::SetStartLine(_)
+            | Command::SetDisplayOffset(_)
+            | Command::SetDisplayMode(_)
+            | Command::EnablePartialDisplay(..)
+            | Command::DisablePartialDisplay
+            | Command::FunctionSelect(_)
+            | Command::SetSleepMode(_)
+            | Command::SetPhaseLengths(..)
+            | Command::SetClockFoscDivset(..)
+            | Command::SetDisplayEnhancements(..)
+            | Command::SetDisplayEnhancementA(..)
+            | Command::SetDisplayEnhancementB(_)
+            | Command::SetGpio(..)
+            | Command::SetSecondPrechargePeriod(_)
+            | Command::SetDefaultGrayScaleTable
+            | Command::SetPreChargeVoltage(_)
+            | Command::SetComDeselectVoltage(_)
+            | Command::SetContrastCurrent(_)
+            | Command::SetMasterContrast(_)
+            | Command::SetMuxRatio(_)
+            | Command::SetCommandLock(_) => (),
+        }
+    }
+
+    #[test]
+    fn display_enhancements_decode_as_enhancement_a() {
+        for (vsl, quality) in [(false, false), (false, true), (true, false), (true, true)] {
+            let prepared = Command::SetDisplayEnhancements(vsl, quality).prepare().unwrap();
+            let expected = Command::SetDisplayEnhancementA(
+                if vsl { VslSource::External } else { VslSource::Internal },
+                if quality { LowGrayScaleQuality::Enhanced } else { LowGrayScaleQuality::Normal },
+            );
+            assert_eq!(Command::decode(prepared.cmd, prepared.args()), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn even_first_phase_length_decodes_as_odd() {
+        let prepared = Command::SetPhaseLengths(6, 4).prepare().unwrap();
+        assert_eq!(Command::decode(prepared.cmd, prepared.args()), Ok(Command::SetPhaseLengths(5, 4)));
+    }
+
+    #[test]
+    fn decode_rejects_bad_input() {
+        assert_eq!(Command::decode(0x01, &[]), Err(CommandError::UnknownCommand));
+        assert_eq!(Command::decode(0xC1, &[]), Err(CommandError::BadArgumentLength));
+        assert_eq!(Command::decode(0xAF, &[0x00]), Err(CommandError::BadArgumentLength));
+        assert_eq!(Command::decode(0x15, &[0, BUF_COL_MAX + 1]), Err(CommandError::OutOfRange));
+        assert_eq!(Command::decode(0xA0, &[0x08, 0x01]), Err(CommandError::OutOfRange));
+        assert_eq!(Command::decode(0xA0, &[0x20, 0x11]), Err(CommandError::OutOfRange));
+        assert_eq!(Command::decode(0xFD, &[0x00]), Err(CommandError::OutOfRange));
+        assert_eq!(Command::decode(0xCA, &[14]), Err(CommandError::OutOfRange));
+    }
+}
@@ -3,9 +3,29 @@
 
 //! This is an SSD1322 Crate
 
+#[cfg(feature = "std")]
+extern crate std;
+
+// Must come first so the logging macros are visible to the other modules
+mod fmt;
+
+pub mod burn_in;
+#[cfg(feature = "capture")]
+pub mod capture;
+#[cfg(feature = "emulator")]
+pub mod emulator;
 pub mod instruction;
-use crate::instruction::{Command, CommandError, CommandData, consts::{BUF_COL_MAX, PIXEL_ROW_MAX}};
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod typestate;
+#[cfg(feature = "wear")]
+pub mod wear;
+use crate::burn_in::BurnInProtection;
+use crate::instruction::{Command, CommandError, CommandData, ComLayout, DisplayEnhancementB, DisplayMode, NibbleRemap, consts::{BUF_COL_MAX, NUM_PIXEL_COLS, PIXEL_ROW_MAX}};
 use core::convert::Infallible;
+use core::ops::RangeInclusive;
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::spi::SpiDevice;
@@ -27,6 +47,49 @@ pub const fn calculate_buffer_size(width: usize, height: usize) -> usize {
     (width * height) / 2
 }
 
+/// Contrast current set by `init_default` unless changed beforehand.
+const DEFAULT_CONTRAST_CURRENT: u8 = 0x3C;
+/// Master contrast set by `init_default` unless changed beforehand.
+const DEFAULT_MASTER_CONTRAST: u8 = 0xA;
+/// Interval between the brightness steps of `fade_to`, in milliseconds.
+const FADE_STEP_MS: u32 = 10;
+
+// Maps a brightness level onto the contrast current and master contrast registers.
+//
+// The segment current is proportional to `current * (master + 1) / 16`. The level is squared (a
+// gamma of 2) so that equal steps in level look like equal steps in brightness, scaled to the
+// range 0..=255 * 16, and then split between the registers using the smallest master contrast that
+// can represent it, which leaves the finest steps for the contrast current.
+const fn brightness_to_contrast(level: u8) -> (u8, u8) {
+    let target = level as u32 * level as u32 * 16 / 255;
+    let mut divisor = target.div_ceil(255);
+    if divisor == 0 {
+        divisor = 1;
+    }
+    ((target / divisor) as u8, (divisor - 1) as u8)
+}
+
+// The inverse of `brightness_to_contrast`, rounding down: the lowest level which drives the
+// most segment current that doesn't exceed the registers', so that mapping the level back gives
+// the same registers whenever they came from `brightness_to_contrast`.
+const fn contrast_to_brightness(current: u8, master: u8) -> u8 {
+    let target = current as u32 * (master as u32 + 1);
+    let (mut level, mut best) = (0, 0);
+    let mut candidate = 1;
+    while candidate <= 255 {
+        let (current, master) = brightness_to_contrast(candidate as u8);
+        let segment_current = current as u32 * (master as u32 + 1);
+        if segment_current > target {
+            break;
+        }
+        if segment_current > best {
+            (level, best) = (candidate, segment_current);
+        }
+        candidate += 1;
+    }
+    level as u8
+}
+
 /// Async SSD1322 OLED display driver.
 ///
 /// This struct provides an interface for controlling the SSD1322 OLED display
@@ -67,6 +130,74 @@ where
     num_rows: u8,
     /// COM line display offset.
     display_offset: u8,
+    /// Display enhancement B setting to apply during initialisation, if any.
+    display_enhancement_b: Option<DisplayEnhancementB>,
+    /// Level at which the size of each flush is logged.
+    flush_log_level: LogLevel,
+    /// Delays used by the power-on and power-off sequences.
+    power_timings: PowerTimings,
+    /// Whether to clear the display RAM during initialisation.
+    clear_on_init: bool,
+    /// The blank mode the display is in, if it is blanked.
+    blanked: Option<DisplayMode>,
+    /// Contrast current register value.
+    contrast_current: u8,
+    /// Master contrast register value.
+    master_contrast: u8,
+    /// Brightness level last set, on the 0-255 scale of `set_brightness`.
+    brightness: u8,
+    /// Average picture level above which `flush_frame` lowers the contrast current.
+    #[cfg(feature = "frame")]
+    apl_limit: Option<u8>,
+    /// Average picture level of the frame last flushed with `flush_frame`, which the APL limiter
+    /// keeps applying to until the next one.
+    #[cfg(feature = "frame")]
+    frame_apl: u8,
+    /// Contrast current sent by the APL limiter in place of `contrast_current`, if any.
+    limited_current: Option<u8>,
+    /// When to dim and sleep the display after the last flush.
+    idle_policy: Option<IdlePolicy>,
+    /// Time since the last flush, in milliseconds.
+    idle_ms: u32,
+    /// Whether the idle policy has dimmed the display.
+    idle_dimmed: bool,
+    /// Whether the idle policy has put the panel to sleep.
+    idle_asleep: bool,
+    /// Burn-in protection settings.
+    burn_in: Option<BurnInProtection>,
+    /// Time since the image was last shifted, in milliseconds.
+    shift_ms: u32,
+    /// Position in the burn-in protection's cycle of shifts.
+    shift_step: u32,
+    /// Display start line register value.
+    start_line: u8,
+    /// Rows the burn-in protection shifts the image down by.
+    shift_rows: u8,
+    /// Column addresses the image in each page of the display RAM is shifted right by.
+    shift_columns: [u8; 2],
+    /// Column addresses the next frame is shifted right by.
+    pending_shift_columns: u8,
+    /// Whether burn-in protection has inverted the display.
+    burn_in_inverted: bool,
+    /// Whether frames are double buffered in the display RAM.
+    page_flipping: bool,
+    /// The page of the display RAM being shown.
+    front_page: u8,
+    /// The height of a page of the display RAM, set by the last flushed frame.
+    page_height: u8,
+    /// The row of the display RAM shown at the top of the panel when scrolling.
+    scroll: u8,
+    /// Order of the 4 pixels in each display RAM column address.
+    nibble_order: NibbleRemap,
+    /// How the panel's rows are wired to the COM lines.
+    com_layout: ComLayout,
+    /// The rows of the panel left lit by `set_active_rows`.
+    active_rows: Option<(u8, u8)>,
+    /// Multiplex ratio, the number of rows scanned.
+    mux: u8,
+    /// The wear map the driver accumulates frames into, if any.
+    #[cfg(feature = "wear")]
+    wear: Option<wear::WearTracker>,
 }
 
 
@@ -77,23 +208,34 @@ where
 ///
 /// # Variants
 ///
-/// - `Standard`: The standard orientation (value: 0x06). 
-///   This corresponds to setting the remap first byte to 0x06 and the second byte to 0x11.
-/// - `Inverted`: The inverted orientation (value: 0x14).
-///   This corresponds to an inverted display mapping.
-#[derive(Clone, Copy, PartialEq)]
+/// The values are the first byte of the remap command with the default nibble order
+/// (`NibbleRemap::Forward`); the second byte is 0x11.
+///
+/// - `Standard`: The standard orientation (value: 0x14).
+/// - `Inverted`: The inverted orientation, rotated 180° (value: 0x06).
+/// - `MirrorHorizontal`: Mirrored left to right (value: 0x16), for example for a panel viewed
+///   through a mirror.
+/// - `MirrorVertical`: Mirrored top to bottom (value: 0x04), for example for a panel reflected in
+///   head-up display glass.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Orientation {
-    /// Standard orientation (0x06).
-    /// Set remap first byte, second byte should be 0x11.
-    Standard = 0x06,
-    /// Inverted orientation (0x14).
-    Inverted = 0x14,
+    /// Standard orientation (0x14).
+    Standard = 0x14,
+    /// Inverted orientation, rotated 180° (0x06).
+    Inverted = 0x06,
+    /// Mirrored left to right (0x16).
+    MirrorHorizontal = 0x16,
+    /// Mirrored top to bottom (0x04).
+    MirrorVertical = 0x04,
 }
 
 /// Configuration structure for the SSD1322 display.
 ///
 /// Controls colour inversion, screen orientation, and display geometry.
 /// The default configuration is suitable for a 256x64 display.
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Config {
     /// Whether the colours are inverted.
     pub inverted_colour: bool,
@@ -107,6 +249,54 @@ pub struct Config {
     /// a non-zero offset — set this to match the panel's datasheet.
     /// Range: 0-127.
     pub display_offset: u8,
+    /// Display enhancement B setting sent during `init_default`. Some module datasheets recommend
+    /// `DisplayEnhancementB::Enhanced`; `None` leaves the controller's reset value untouched.
+    pub display_enhancement_b: Option<DisplayEnhancementB>,
+    /// Level at which the size of each flush is logged when the `defmt` or `log` feature is
+    /// enabled. Commands are always logged at trace level.
+    pub flush_log_level: LogLevel,
+    /// Delays used by the power-on and power-off sequences.
+    pub power_timings: PowerTimings,
+    /// Whether initialisation clears the whole display RAM while the panel is still asleep, so
+    /// that it doesn't show the random power-on contents of the RAM when it is turned on. Off by
+    /// default, as writing the whole RAM over a slow bus delays initialisation noticeably.
+    pub clear_on_init: bool,
+    /// Average picture level (see `FrameStats::apl`) above which `flush_frame` lowers the contrast
+    /// current, in proportion, to limit the panel's supply current. `None` disables the limiter.
+    pub apl_limit: Option<u8>,
+    /// When to dim and sleep the display after the last flush. `None` disables the idle policy.
+    /// See `SSD1322::tick`.
+    pub idle_policy: Option<IdlePolicy>,
+    /// Pixel shifting and inversion to spread the wear of a static image. `None` disables burn-in
+    /// protection. See `SSD1322::tick`.
+    pub burn_in: Option<BurnInProtection>,
+    /// Whether `flush_frame` double buffers frames: each frame is written to the half of the
+    /// display RAM that isn't being shown, and then shown all at once by moving the start line, so
+    /// that the panel never shows a partly written frame. Requires frames at most 64 rows high.
+    /// Burn-in protection doesn't shift the image vertically when this is enabled.
+    pub page_flipping: bool,
+    /// Order of the 4 pixels stored at each display RAM column address. `NibbleRemap::Forward`
+    /// matches the default packing of `Frame`; with `NibbleRemap::Reverse`, call
+    /// `Frame::set_nibble_order` on frames before flushing them.
+    pub nibble_order: NibbleRemap,
+    /// How the panel's rows are wired to the COM lines, sent with every remapping command. Most
+    /// 256x64 modules use `ComLayout::DualProgressive`; check the module's datasheet if every
+    /// other row looks scrambled.
+    pub com_layout: ComLayout,
+}
+
+/// Log level used for the driver's optional `defmt`/`log` instrumentation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LogLevel {
+    /// Don't log.
+    Off,
+    /// Log at trace level.
+    Trace,
+    /// Log at debug level.
+    Debug,
+    /// Log at info level.
+    Info,
 }
 
 impl Default for Config {
@@ -116,6 +306,77 @@ impl Default for Config {
             orientation: Orientation::Standard,
             num_rows: 64,
             display_offset: 0,
+            display_enhancement_b: None,
+            flush_log_level: LogLevel::Trace,
+            power_timings: PowerTimings::default(),
+            clear_on_init: false,
+            apl_limit: None,
+            idle_policy: None,
+            burn_in: None,
+            page_flipping: false,
+            nibble_order: NibbleRemap::Forward,
+            com_layout: ComLayout::DualProgressive,
+        }
+    }
+}
+
+/// Delays used by the power-on and power-off sequences described in section 8.9 of the SSD1322
+/// Manual.
+///
+/// The defaults are the datasheet minimums, which suit most modules. Modules whose VCC boost
+/// converter is slow to start may need a longer `vcc_rise_us`, and a `display_on_settle_ms` to
+/// hide the panel's start-up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PowerTimings {
+    /// Time for the internal VDD to stabilise after VCI and VDDIO are powered on (t0), in
+    /// microseconds. Datasheet minimum: 1ms.
+    pub t0_us: u32,
+    /// Time the RES# pin is held low (t1), in microseconds. Datasheet minimum: 100µs.
+    pub t1_us: u32,
+    /// Time between releasing the RES# pin and powering on VCC (t2), in microseconds. Datasheet
+    /// minimum: 100µs.
+    pub t2_us: u32,
+    /// Time for VCC to rise after the power on pin is set high, before any command is sent, in
+    /// microseconds. Not specified by the datasheet, as it depends on the module's VCC supply.
+    pub vcc_rise_us: u32,
+    /// Time to wait after the display is turned on for SEG/COM to stabilise, in milliseconds. The
+    /// datasheet specifies 200ms. `None` returns as soon as the display ON command is sent.
+    pub display_on_settle_ms: Option<u32>,
+    /// Time to wait after powering off VCC before VCI and VDDIO may be powered off (tOFF), in
+    /// milliseconds. Datasheet minimum: 100ms.
+    pub t_off_ms: u32,
+}
+
+/// When to dim the display, and put the panel to sleep, after the last flush.
+///
+/// The driver doesn't keep time itself: the application reports the time passing with
+/// `SSD1322::tick` or `SSD1322::tick_delay`. The next flush writes its image, then wakes the
+/// panel and restores the brightness.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IdlePolicy {
+    /// Time without a flush after which the display is dimmed, in milliseconds. `None` never
+    /// dims the display.
+    pub dim_after_ms: Option<u32>,
+    /// The brightness to dim to, on the 0-255 scale of `SSD1322::set_brightness`. The display is
+    /// only dimmed if this lowers the segment current in use, after any lowering by the APL
+    /// limiter.
+    pub dim_brightness: u8,
+    /// Time without a flush after which the panel is put to sleep, in milliseconds. `None` never
+    /// puts the panel to sleep.
+    pub sleep_after_ms: Option<u32>,
+}
+
+impl Default for PowerTimings {
+    fn default() -> Self {
+        Self {
+            t0_us: 1_000,
+            t1_us: 100,
+            t2_us: 100,
+            vcc_rise_us: 1_000,
+            display_on_settle_ms: None,
+            t_off_ms: 100,
         }
     }
 }
@@ -138,6 +399,41 @@ where
             orientation: config.orientation,
             num_rows: config.num_rows,
             display_offset: config.display_offset,
+            display_enhancement_b: config.display_enhancement_b,
+            flush_log_level: config.flush_log_level,
+            power_timings: config.power_timings,
+            clear_on_init: config.clear_on_init,
+            blanked: None,
+            contrast_current: DEFAULT_CONTRAST_CURRENT,
+            master_contrast: DEFAULT_MASTER_CONTRAST,
+            brightness: contrast_to_brightness(DEFAULT_CONTRAST_CURRENT, DEFAULT_MASTER_CONTRAST),
+            #[cfg(feature = "frame")]
+            apl_limit: config.apl_limit,
+            #[cfg(feature = "frame")]
+            frame_apl: 0,
+            limited_current: None,
+            idle_policy: config.idle_policy,
+            idle_ms: 0,
+            idle_dimmed: false,
+            idle_asleep: false,
+            burn_in: config.burn_in,
+            shift_ms: 0,
+            shift_step: 0,
+            start_line: 0,
+            shift_rows: 0,
+            shift_columns: [0; 2],
+            pending_shift_columns: 0,
+            burn_in_inverted: false,
+            page_flipping: config.page_flipping,
+            front_page: 0,
+            page_height: 0,
+            scroll: 0,
+            nibble_order: config.nibble_order,
+            com_layout: config.com_layout,
+            active_rows: None,
+            mux: config.num_rows,
+            #[cfg(feature = "wear")]
+            wear: None,
         }
     }
 
@@ -149,15 +445,26 @@ where
     /// If you do not want to use the default configuration, you can set up the display with the following pattern:
     ///
     /// ```
-    /// # use ssd1322_rs::{SSD1322, CommandData, DelayNs, Error};
-    /// # async fn example_usage<D>(display: &mut SSD1322<SPI, DC, RST, PO>, delay: &mut D) -> Result<(), Error<E>>
+    /// # use ssd1322_rs::{SSD1322, Error};
+    /// # use ssd1322_rs::instruction::Command;
+    /// # use embedded_hal_async::delay::DelayNs;
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E, D>(display: &mut SSD1322<SPI, DC, RST, PO>, delay: &mut D) -> Result<(), Error<E>>
     /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
     /// #     D: DelayNs,
     /// # {
-    ///     display.hard_reset(&mut delay).await?; // Must be called first
+    ///     display.hard_reset(delay).await?; // Must be called first
     ///
-    ///     display.write_command(Command::SetStartLine(0).prepare()?).await?;
-    ///     display.write_command(Command::SetDisplayOffset(0).prepare()?).await?;
+    ///     for command in [Command::SetStartLine(0), Command::SetDisplayOffset(0)] {
+    ///         let command = command.prepare()?;
+    ///         display.write_raw_command(command.cmd, command.args()).await?;
+    ///     }
     ///     /*Add other Display commands here */
     /// # Ok(())
     /// # }
@@ -173,11 +480,69 @@ where
     pub async fn init_default<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
     where
         D: DelayNs,
+    {
+        self.init(delay, None).await
+    }
+
+    /// Initialises the display in the same way as [`init_default`](Self::init_default), but writes
+    /// a splash image to the display RAM before the panel is turned on, so that the splash is the
+    /// first thing shown.
+    ///
+    /// # Parameters
+    ///
+    /// - `delay`: A mutable reference to an implementation of the `DelayNs` trait.
+    /// - `splash`: A packed 4bpp buffer covering the whole panel, in the same format as accepted
+    ///   by `flush_buffer`. Its height is `Config::num_rows`, and its width is derived from its
+    ///   length.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the initialization succeeds, or an `Error` if it fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::CommandError(CommandError::OutOfRange)`: If `splash` is empty, or its length
+    ///   doesn't describe a whole number of rows, each a multiple of 4 pixels wide and no wider
+    ///   than 480 pixels. Nothing is sent to the display.
+    pub async fn init_with_splash<D>(&mut self, delay: &mut D, splash: &[u8]) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.init(delay, Some(splash)).await
+    }
 
+    // Sends the initialisation sequence, writing the display RAM while the panel is still asleep
+    async fn init<D>(&mut self, delay: &mut D, splash: Option<&[u8]>) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
     {
         use crate::instruction::*;
+        // Check the splash first, so that a bad one leaves the display untouched
+        let splash = splash.map(|splash| self.splash_width(splash).map(|width| (width, splash))).transpose()?;
         self.hard_reset(delay).await?;
-        let dc = &mut self.dc;
+        self.blanked = None;
+        #[cfg(feature = "frame")]
+        {
+            self.frame_apl = 0;
+        }
+        #[cfg(feature = "wear")]
+        self.forget_wear();
+        self.limited_current = None;
+        self.idle_ms = 0;
+        self.idle_dimmed = false;
+        self.idle_asleep = false;
+        self.shift_ms = 0;
+        self.shift_step = 0;
+        self.start_line = 0;
+        self.shift_rows = 0;
+        self.shift_columns = [0; 2];
+        self.pending_shift_columns = 0;
+        self.burn_in_inverted = false;
+        self.front_page = 0;
+        self.page_height = 0;
+        self.scroll = 0;
+        self.active_rows = None;
+        self.mux = self.num_rows;
         let inverted = self.inverted;
 
 
@@ -186,9 +551,9 @@ where
             Command::SetSleepMode(true).prepare()?,
             Command::SetRemapping(IncrementAxis::Horizontal,
                 ColumnRemap::Forward,
-                NibbleRemap::Forward,
+                self.nibble_order,
                 ComScanDirection::RowZeroLast,
-                ComLayout::DualProgressive,
+                self.com_layout,
             ).prepare()?,
             Command::SetStartLine(0).prepare()?,
             Command::SetDisplayOffset(self.display_offset).prepare()?,
@@ -199,44 +564,52 @@ where
             Command::FunctionSelect(FunctionSelection::InternalVDD).prepare()?,
             Command::SetPhaseLengths(5, 15).prepare()?,
             Command::SetClockFoscDivset(10, 1).prepare()?,
-            Command::SetDisplayEnhancements(true, true).prepare()?,
+            Command::SetDisplayEnhancementA(VslSource::External, LowGrayScaleQuality::Enhanced).prepare()?,
             Command::SetSecondPrechargePeriod(8).prepare()?,
             Command::SetDefaultGrayScaleTable.prepare()?,
             Command::SetPreChargeVoltage(31).prepare()?,
             Command::SetComDeselectVoltage(7).prepare()?,
-            Command::SetContrastCurrent(0x3C).prepare()?,
-            Command::SetMasterContrast(0xA).prepare()?,
+            Command::SetContrastCurrent(self.contrast_current).prepare()?,
+            Command::SetMasterContrast(self.master_contrast).prepare()?,
             Command::SetMuxRatio(self.num_rows - 1).prepare()?,
             Command::DisablePartialDisplay.prepare()?,
-            // Don't bother setting DisplayB enhancements,
-            Command::SetSleepMode(false).prepare()?,
         ];
 
-        for CommandData {
-            cmd,
-            data,
-            len
-        } in commands
-        {
-            dc.set_low().ok();
-            let mut tx_data = [0_u8; 1];
-            tx_data.copy_from_slice(&[cmd as u8]);
-            self.spi.write(&tx_data).await.map_err(Error::Comm)?;
-            if !data.is_empty() {
-                dc.set_high().ok();
-                let mut buf = [0_u8; 2];
-                buf.copy_from_slice(&data);
-                self.spi
-                    .write(&buf[..len])
-                    .await
-                    .map_err(Error::Comm)?;
-            }
+        for command in commands {
+            self.write_command(&command).await?;
+        }
+
+        if let Some(enhancement) = self.display_enhancement_b {
+            self.write_command(&Command::SetDisplayEnhancementB(enhancement).prepare()?).await?;
         }
 
+        // The remapping affects how the RAM is written, so set it before writing
         self.set_orientation(self.orientation).await?;
+        if self.clear_on_init {
+            self.clear_ram().await?;
+        }
+        if let Some((width, splash)) = splash {
+            self.set_address_window(0, 0, width, self.num_rows as u16).await?;
+            self.write_command(&Command::WriteRam.prepare()?).await?;
+            self.write_data(splash).await?;
+        }
+
+        self.write_command(&Command::SetSleepMode(false).prepare()?).await?;
+        self.settle(delay).await;
         Ok(())
     }
 
+    // Helper function to work out the width of a splash image from its length, which must be a
+    // whole number of rows, each a multiple of 4 pixels wide and no wider than 480 pixels
+    fn splash_width(&self, splash: &[u8]) -> Result<u16, Error<E>> {
+        let rows = self.num_rows as usize;
+        let width = splash.len() * 2 / rows;
+        if width == 0 || (splash.len() * 2) % rows != 0 || width % 4 != 0 || width > 480 {
+            return Err(Error::CommandError(CommandError::OutOfRange));
+        }
+        Ok(width as u16)
+    }
+
     /// Performs a hard reset power-on sequence as described in section 8.9 of the SSD1322 Manual.
     ///
     /// The power-on sequence is as follows:
@@ -246,6 +619,9 @@ where
     /// 3. Set the RES# pin LOW (logic low) for at least 100µs (t1) and then HIGH (logic high).
     /// 4. After setting the RES# pin LOW, wait for at least 100µs (t2), then power ON VCC.
     ///
+    /// The delays are taken from `Config::power_timings`, and a further `vcc_rise_us` is waited
+    /// after powering on VCC.
+    ///
     /// The datasheet suggests sending command AFh to turn the display ON, and SEG/COM will be ON after 200ms.
     /// However, instead of turning on the display immediately, we configure the screen before turning it on.
     ///
@@ -260,33 +636,133 @@ where
     where
         D: DelayNs,
     {
-        delay.delay_ms(1).await;
+        let timings = self.power_timings;
+        delay.delay_us(timings.t0_us).await;
         self.rst.set_low().map_err(Error::Pin)?;
-        delay.delay_ms(1).await;
+        delay.delay_us(timings.t1_us).await;
         self.rst.set_high().map_err(Error::Pin)?;
-        delay.delay_ms(1).await;
+        delay.delay_us(timings.t2_us).await;
         self.power.set_high().map_err(Error::Pin)?;
-        delay.delay_ms(1).await;
+        delay.delay_us(timings.vcc_rise_us).await;
+        Ok(())
+    }
+
+    /// Performs the power-off sequence described in section 8.9 of the SSD1322 Manual.
+    ///
+    /// The power-off sequence is as follows:
+    ///
+    /// 1. Send command AEh to turn the display OFF (sleep mode on).
+    /// 2. Power OFF VCC, by setting the power on pin low.
+    /// 3. Wait at least 100ms (tOFF, set by `PowerTimings::t_off_ms`) before powering OFF VCI and
+    ///    VDDIO.
+    ///
+    /// VCC must always be switched off before VCI and VDDIO. This driver does not control VCI or
+    /// VDDIO, so if your product switches them off, do so only after this function returns. Cutting
+    /// VCC without sending the display OFF command first, or cutting VDD before VCC, shortens the
+    /// lifetime of the panel.
+    ///
+    /// The display RAM and registers are kept as long as VCI and VDDIO stay on, so the display can
+    /// be brought back with [`power_on`](Self::power_on). If they were switched off, re-initialise
+    /// the display instead.
+    ///
+    /// # Parameters
+    ///
+    /// - `delay`: A mutable reference to an implementation of the `DelayNs` trait, used to introduce delays in the sequence.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the power-off sequence completes successfully, or an `Error` if it fails.
+    pub async fn shutdown<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.write_command(&Command::SetSleepMode(true).prepare()?).await?;
+        self.power.set_low().map_err(Error::Pin)?;
+        delay.delay_ms(self.power_timings.t_off_ms).await;
+        Ok(())
+    }
+
+    /// Powers the display back on after [`shutdown`](Self::shutdown).
+    ///
+    /// This switches VCC back on by setting the power on pin high, waits `PowerTimings::vcc_rise_us`
+    /// for it to stabilise, and then sends command AFh to turn the display ON, waiting
+    /// `PowerTimings::display_on_settle_ms` afterwards if set. VCI and VDDIO must already be on, and must
+    /// have stayed on since the shutdown; otherwise use [`init_default`](Self::init_default).
+    ///
+    /// # Parameters
+    ///
+    /// - `delay`: A mutable reference to an implementation of the `DelayNs` trait, used to introduce delays in the sequence.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the power-on sequence completes successfully, or an `Error` if it fails.
+    pub async fn power_on<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.power.set_high().map_err(Error::Pin)?;
+        delay.delay_us(self.power_timings.vcc_rise_us).await;
+        self.write_command(&Command::SetSleepMode(false).prepare()?).await?;
+        self.idle_asleep = false;
+        self.settle(delay).await;
+        self.wake_from_idle().await
+    }
+
+    // Helper function to wait for SEG/COM to stabilise after the display is turned on
+    async fn settle<D>(&mut self, delay: &mut D)
+    where
+        D: DelayNs,
+    {
+        if let Some(ms) = self.power_timings.display_on_settle_ms {
+            delay.delay_ms(ms).await;
+        }
+    }
+
+    /// Clears the whole 480x128 display RAM, including the parts outside the panel.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the display RAM is cleared, or an `Error` if the operation fails.
+    pub async fn clear_ram(&mut self) -> Result<(), Error<E>> {
+        // One row of the display RAM: two bytes per column address
+        const ROW: [u8; 2 * (BUF_COL_MAX as usize + 1)] = [0; 2 * (BUF_COL_MAX as usize + 1)];
+
+        self.write_command(&Command::SetColumnAddress(0, BUF_COL_MAX).prepare()?).await?;
+        self.write_command(&Command::SetRowAddress(0, PIXEL_ROW_MAX).prepare()?).await?;
+        self.write_command(&Command::WriteRam.prepare()?).await?;
+        for _ in 0..=PIXEL_ROW_MAX {
+            self.write_data(&ROW).await?;
+        }
         Ok(())
     }
 
     /// Configures the orientation of the display.
     ///
-    /// This function sets the orientation of the display to either standard or inverted. 
-    /// It updates the internal orientation state and sends the appropriate command to the display 
-    /// to adjust the remapping configuration.
+    /// This function sets the orientation of the display to standard, inverted (rotated 180°), or
+    /// mirrored horizontally or vertically. It updates the internal orientation state and sends the
+    /// appropriate command to the display to adjust the remapping configuration, using the nibble
+    /// order from `Config::nibble_order` and the COM layout from `Config::com_layout`.
     ///
     /// # Parameters
     ///
-    /// - `orientation`: The desired orientation for the display. Can be either `Orientation::Standard` or `Orientation::Inverted`.
+    /// - `orientation`: The desired orientation for the display.
     ///
     /// # Returns
     ///
     /// A `Result` which is `Ok` if the orientation is successfully set, or an `Error` if the operation fails.
     ///
     /// ```
-    /// # use your_crate::{ssd1322_rs, Orientation, Error};
-    /// # async fn example_usage(display: &mut SSD1322<SPI, DC, RST, PO>) -> Result<(), Error<E>> {
+    /// # use ssd1322_rs::{SSD1322, Orientation, Error};
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E>(display: &mut SSD1322<SPI, DC, RST, PO>) -> Result<(), Error<E>>
+    /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
+    /// # {
     /// display.set_orientation(Orientation::Inverted).await?;
     /// # Ok(())
     /// # }
@@ -294,211 +770,1008 @@ where
     pub async fn set_orientation(&mut self, orientation: Orientation) -> Result<(), Error<E>> {
         use crate::instruction::*;
         self.orientation = orientation;
-        if orientation == Orientation::Inverted{
-            self.write_command(&Command::SetRemapping(
-                IncrementAxis::Horizontal,
-                ColumnRemap::Reverse,
-                NibbleRemap::Forward,
-                ComScanDirection::RowZeroFirst,
-                ComLayout::DualProgressive,
-            ).prepare()?).await?;
-        } else {
-            self.write_command(&Command::SetRemapping(
-                IncrementAxis::Horizontal,
-                ColumnRemap::Forward,
-                NibbleRemap::Forward,
-                ComScanDirection::RowZeroLast,
-                ComLayout::DualProgressive,
-            ).prepare()?).await?;
+        let (column_remap, com_scan_direction) = match orientation {
+            Orientation::Standard => (ColumnRemap::Forward, ComScanDirection::RowZeroLast),
+            Orientation::Inverted => (ColumnRemap::Reverse, ComScanDirection::RowZeroFirst),
+            Orientation::MirrorHorizontal => (ColumnRemap::Reverse, ComScanDirection::RowZeroLast),
+            Orientation::MirrorVertical => (ColumnRemap::Forward, ComScanDirection::RowZeroFirst),
+        };
+        self.write_command(&Command::SetRemapping(
+            IncrementAxis::Horizontal,
+            column_remap,
+            self.nibble_order,
+            com_scan_direction,
+            self.com_layout,
+        ).prepare()?).await?;
+        // The scan rows of the active rows depend on the COM scan direction
+        if let Some((start, end)) = self.active_rows {
+            self.set_active_rows(start..=end).await?;
         }
         Ok(())
     }
 
-    /// Sends a command to the SSD1322 display.
+    /// Inverts the colours of the display, or restores them.
     ///
-    /// This function writes a command to the SSD1322 display using SPI communication. 
-    /// It first sets the data/command pin low to indicate that a command is being sent, 
-    /// writes the command, and if there is any associated data, it sets the data/command 
-    /// pin high and writes the data.
+    /// The image in the display RAM is not changed: the controller inverts the grayscale level of
+    /// every pixel as it is shown. If the display is blanked, the setting takes effect when it is
+    /// unblanked.
     ///
     /// # Parameters
     ///
-    /// - `command`: A reference to a `CommandData` struct containing the command byte and optional data bytes.
+    /// - `inverted`: `true` to invert the colours, `false` to show them normally.
     ///
     /// # Returns
     ///
-    /// A `Result` which is `Ok` if the command is successfully written, or an `Error` if the operation fails.
-    ///
-    /// # Errors
-    ///
-    /// - `Error::Comm`: If there is a communication error during the SPI write operation.
-    async fn write_command(
-        &mut self,
-        command: &CommandData,
-    ) -> Result<(), Error<E>> {
-        let dc = &mut self.dc;
-        dc.set_low().ok();
-
-        self.spi.write(&[command.cmd]).await.map_err(Error::Comm)?;
-
-        if command.len != 0 {
-            dc.set_high().ok();
-            self.spi
-                .write(&command.data[..command.len])
-                .await
-                .map_err(Error::Comm)?;
+    /// A `Result` which is `Ok` if the colours are successfully set, or an `Error` if the operation fails.
+    pub async fn set_inverted(&mut self, inverted: bool) -> Result<(), Error<E>> {
+        self.inverted = inverted;
+        if self.blanked.is_none() {
+            self.write_command(&Command::SetDisplayMode(self.display_mode()).prepare()?).await?;
         }
         Ok(())
     }
 
-    // Helper function to set the DC pin high ready for Data transmission
-    fn start_data(&mut self) -> Result<(), Error<E>> {
-        // Write command isnt in here because this isnt an async function
-        self.dc.set_high().map_err(Error::Pin)
-    }
-
-    /// Sends data to the SSD1322 display.
-    ///
-    /// This function sends data from a buffer to the screen. It assumes the correct initialisation 
-    /// commands have already been sent, and that the address window has been set/
+    /// Blanks the display, turning every pixel off or fully on without changing the display RAM.
     ///
     /// # Parameters
     ///
-    /// - `data`: A reference to a buffer containing the pixel data.
+    /// - `mode`: Either `DisplayMode::BlankDark` or `DisplayMode::BlankBright`.
     ///
     /// # Returns
     ///
-    /// A `Result` which is `Ok` if the command is successfully written, or an `Error` if the operation fails.
+    /// A `Result` which is `Ok` if the display is successfully blanked, or an `Error` if the operation fails.
     ///
     /// # Errors
     ///
-    /// - `Error::Comm`: If there is a communication error during the SPI write operation.
-    pub async fn write_data(&mut self, data: &[u8]) -> Result<(), Error<E>> {
-        self.start_data()?;
-        self.spi.write(&data).await.map_err(Error::Comm)
+    /// - `Error::CommandError(CommandError::OutOfRange)`: If `mode` is not a blank mode. Use
+    ///   `unblank` and `set_inverted` to choose between the normal and inverse modes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ssd1322_rs::{SSD1322, Error};
+    /// # use ssd1322_rs::instruction::DisplayMode;
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E>(display: &mut SSD1322<SPI, DC, RST, PO>) -> Result<(), Error<E>>
+    /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
+    /// # {
+    /// display.blank(DisplayMode::BlankDark).await?;
+    /// /* ... */
+    /// display.unblank().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn blank(&mut self, mode: DisplayMode) -> Result<(), Error<E>> {
+        if !matches!(mode, DisplayMode::BlankDark | DisplayMode::BlankBright) {
+            return Err(Error::CommandError(CommandError::OutOfRange));
+        }
+        self.write_command(&Command::SetDisplayMode(mode).prepare()?).await?;
+        self.blanked = Some(mode);
+        Ok(())
     }
 
-    /// Sets the address window for the display.
+    /// Restores the image after `blank`, in normal or inverse colours as set by `set_inverted`.
     ///
-    /// This function defines a rectangular area (address window) on the display where subsequent drawing operations will be applied.
-    /// It calculates the necessary column and row addresses based on the provided dimensions and starting coordinates.
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the display is successfully unblanked, or an `Error` if the operation fails.
+    pub async fn unblank(&mut self) -> Result<(), Error<E>> {
+        self.blanked = None;
+        self.write_command(&Command::SetDisplayMode(self.display_mode()).prepare()?).await
+    }
+
+    /// Returns the display mode the display is currently in.
+    pub fn display_mode(&self) -> DisplayMode {
+        match self.blanked {
+            Some(mode) => mode,
+            None if self.inverted => DisplayMode::Inverse,
+            None => DisplayMode::Normal,
+        }
+    }
+
+    /// Leaves only a band of rows of the panel lit, to save power when showing a small image such
+    /// as an always-on clock.
+    ///
+    /// The controller stops driving the rows outside of `rows` (partial display mode), and scans no
+    /// further than it must to reach them, by lowering the multiplex ratio. Scanning fewer rows
+    /// lights each of them for longer, so the contrast current is lowered in proportion to keep
+    /// their brightness the same, which is where most of the power is saved. Rows are only scanned
+    /// from `COM0`, which drives the bottom row of the panel, so the saving is largest when the
+    /// band is at the bottom of the panel: the bottom of the image for `Orientation::Standard` and
+    /// `Orientation::MirrorHorizontal`, or the top of the image for `Orientation::Inverted` and
+    /// `Orientation::MirrorVertical`.
+    ///
+    /// The image in the display RAM is not changed, and flushes continue to write the whole image.
+    /// `set_all_rows_active` restores the whole panel. Rows that `Config::display_offset` moves
+    /// past the multiplex ratio stay dark, as they are with the whole panel lit.
     ///
     /// # Parameters
     ///
-    /// - `start_x`: The starting x-coordinate (horizontal) of the address window.
-    /// - `start_y`: The starting y-coordinate (vertical) of the address window.
-    /// - `width`: The width of the address window.
-    /// - `height`: The height of the address window.
+    /// - `rows`: The rows of the panel to leave lit, from the top of the image.
     ///
     /// # Returns
     ///
-    /// A `Result` which is `Ok` if the address window is successfully set, or an `Error` if the operation fails.
+    /// A `Result` which is `Ok` if the rows are successfully set, or an `Error` if the operation fails.
     ///
     /// # Errors
     ///
-    /// - `Error::CommandError(CommandError::OutOfRange)`: If the calculated column or row addresses are out of the valid range.
+    /// - `Error::CommandError(CommandError::OutOfRange)`: If `rows` is empty, ends below the
+    ///   last row of the panel (`Config::num_rows`), or only holds rows that `Config::display_offset`
+    ///   leaves dark, or if `Config::display_offset` plus `Config::num_rows` is more than 128.
     ///
     /// # Example
     ///
     /// ```
     /// # use ssd1322_rs::{SSD1322, Error};
-    /// # async fn example_usage(display: &mut SSD1322<SPI, DC, RST, PO>) -> Result<(), Error<E>> {
-    /// display.set_address_window(0, 0, 256, 64).await?;
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E>(display: &mut SSD1322<SPI, DC, RST, PO>) -> Result<(), Error<E>>
+    /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
+    /// # {
+    /// // Keep the clock in the top 16 rows lit overnight
+    /// display.set_active_rows(0..=15).await?;
+    /// /* ... */
+    /// display.set_all_rows_active().await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn set_address_window(
-        &mut self,
-        start_x: u16,
-        start_y: u16,
-        width: u16,
-        height: u16,
-    ) -> Result<(), Error<E>> {
-        let offset = (480-width+start_x) / 2;
-        let column_start = offset / 4;
-        let column_end = (column_start + (width / 4)) - 1;
+    pub async fn set_active_rows(&mut self, rows: RangeInclusive<u8>) -> Result<(), Error<E>> {
+        let (start, end) = (*rows.start(), *rows.end());
+        let offset = self.display_offset;
+        if start > end || end >= self.num_rows || self.num_rows as u16 + offset as u16 > PIXEL_ROW_MAX as u16 + 1 {
+            return Err(Error::CommandError(CommandError::OutOfRange));
+        }
+        // Image row `m` is on `COM[num_rows - 1 - m]` when scanning from `COM[N-1]` to `COM0`, and
+        // on `COMm` otherwise, moved `offset` COM lines up by the display offset. The multiplex
+        // ratio must reach the band's COM lines, and the controller scans at least 16 rows. Lines
+        // past `num_rows` are dark with the whole panel lit, so they stay dark.
+        let (scan_start, scan_end) = if self.row_zero_first() {
+            if start + offset >= self.num_rows {
+                return Err(Error::CommandError(CommandError::OutOfRange));
+            }
+            self.mux = (end + offset + 1).max(16).min(self.num_rows);
+            (start + offset, (end + offset).min(self.mux - 1))
+        } else {
+            if end < offset {
+                return Err(Error::CommandError(CommandError::OutOfRange));
+            }
+            self.mux = (self.num_rows - start + offset).max(16).min(self.num_rows);
+            // Scan row 0 is on `COM[mux - 1]`, which is `num_rows - mux` rows below the top, and
+            // shows image row `offset` with the whole panel lit
+            let hidden = self.num_rows - self.mux + offset;
+            (start.saturating_sub(hidden), end - hidden)
+        };
+        self.write_command(&Command::SetMuxRatio(self.mux - 1).prepare()?).await?;
+        self.write_command(&Command::EnablePartialDisplay(scan_start, scan_end).prepare()?).await?;
+        self.active_rows = Some((start, end));
+        self.update_start_line().await?;
+        self.write_scan_current().await
+    }
 
-        if column_start > BUF_COL_MAX.into() || column_end > BUF_COL_MAX.into() ||
-        start_y > PIXEL_ROW_MAX.into() || height > PIXEL_ROW_MAX.into() {
-         return Err(Error::CommandError(CommandError::OutOfRange))
+    /// Lights the whole panel again after `set_active_rows`, restoring the multiplex ratio and
+    /// contrast current.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the rows are successfully restored, or an `Error` if the operation fails.
+    pub async fn set_all_rows_active(&mut self) -> Result<(), Error<E>> {
+        self.mux = self.num_rows;
+        self.write_command(&Command::SetMuxRatio(self.mux - 1).prepare()?).await?;
+        self.write_command(&Command::DisablePartialDisplay.prepare()?).await?;
+        self.active_rows = None;
+        self.update_start_line().await?;
+        self.write_scan_current().await
+    }
+
+    /// Returns the rows of the panel left lit by `set_active_rows`, or `None` if the whole panel
+    /// is lit.
+    pub fn active_rows(&self) -> Option<RangeInclusive<u8>> {
+        self.active_rows.map(|(start, end)| start..=end)
+    }
+
+    // Whether the orientation scans the rows from `COM0` to `COM[N-1]`
+    fn row_zero_first(&self) -> bool {
+        matches!(self.orientation, Orientation::Inverted | Orientation::MirrorVertical)
+    }
+
+    // Scales a contrast current for the multiplex ratio, so that rows are as bright with fewer
+    // rows scanned
+    fn scan_current(&self, current: u8) -> u8 {
+        let rows = self.num_rows as u16;
+        ((current as u16 * self.mux as u16 + rows / 2) / rows) as u8
+    }
+
+    // Helper function to work out the contrast current the APL limiter allows in place of
+    // `current` for the frame last flushed
+    fn apl_limited_current(&self, current: u8) -> u8 {
+        #[cfg(feature = "frame")]
+        if let Some(limit) = self.apl_limit.filter(|&limit| self.frame_apl > limit) {
+            return (current as u16 * limit as u16 / self.frame_apl as u16) as u8;
         }
+        current
+    }
 
-        self.write_command(&Command::SetColumnAddress(column_start as u8, column_end as u8).prepare()?).await?;
-        self.write_command(&Command::SetRowAddress(start_y as u8, (height-1) as u8).prepare()?).await
+    // Sends the contrast current in use, unless the idle policy has dimmed the display
+    async fn write_scan_current(&mut self) -> Result<(), Error<E>> {
+        if !self.idle_dimmed {
+            let current = self.scan_current(self.limited_current.unwrap_or(self.contrast_current));
+            self.write_command(&Command::SetContrastCurrent(current).prepare()?).await?;
+        }
+        Ok(())
     }
 
-    /// Flushes the provided buffer to the display.
+    /// Sets the contrast current and master contrast registers directly.
     ///
-    /// This function writes the contents of the provided buffer to the display's RAM.
-    /// It sends the `WriteRam` command to the display, followed by the buffer data.
+    /// The segment current is proportional to `current * (master + 1) / 16`. Prefer
+    /// `set_brightness` unless you need to set the registers to specific values.
+    ///
+    /// If the APL limiter (see `Config::apl_limit`) is lowering the contrast current for the frame
+    /// on the display, `current` is lowered in proportion too.
     ///
     /// # Parameters
     ///
-    /// - `buf`: A slice containing the data to be written to the display.
+    /// - `current`: The contrast current, 0-255.
+    /// - `master`: The master contrast, 0-15.
     ///
     /// # Returns
     ///
-    /// A `Result` which is `Ok` if the buffer is successfully flushed to the display, or an `Error` if the operation fails.
+    /// A `Result` which is `Ok` if the contrast is successfully set, or an `Error` if the operation fails.
     ///
     /// # Errors
     ///
-    /// - `Error::Comm`: If there is a communication error during the SPI write operation.
+    /// - `Error::CommandError(CommandError::OutOfRange)`: If `master` is greater than 15.
+    pub async fn set_contrast(&mut self, current: u8, master: u8) -> Result<(), Error<E>> {
+        let master_command = Command::SetMasterContrast(master).prepare()?;
+        let limited = self.apl_limited_current(current);
+        self.write_command(&Command::SetContrastCurrent(self.scan_current(limited)).prepare()?).await?;
+        self.write_command(&master_command).await?;
+        self.contrast_current = current;
+        self.master_contrast = master;
+        self.limited_current = (limited != current).then_some(limited);
+        self.idle_dimmed = false;
+        self.brightness = contrast_to_brightness(current, master);
+        Ok(())
+    }
+
+    /// Returns the contrast current and master contrast registers, as last set.
+    pub fn contrast(&self) -> (u8, u8) {
+        (self.contrast_current, self.master_contrast)
+    }
+
+    /// Sets the brightness of the display.
+    ///
+    /// The level is mapped onto the contrast current and master contrast registers with a gamma
+    /// of 2, so that equal steps in level look like roughly equal steps in brightness. Level 255 is
+    /// the maximum segment current, and level 0 turns the segment current off.
+    ///
+    /// # Parameters
+    ///
+    /// - `level`: The brightness, 0-255.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the brightness is successfully set, or an `Error` if the operation fails.
     ///
     /// # Example
     ///
     /// ```
     /// # use ssd1322_rs::{SSD1322, Error};
-    /// # async fn example_usage(display: &mut SSD1322<SPI, DC, RST, PO>, buffer: &[u8]) -> Result<(), Error<E>> {
-    /// display.flush_buffer(buffer).await?;
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::delay::DelayNs;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E, D>(display: &mut SSD1322<SPI, DC, RST, PO>, delay: &mut D) -> Result<(), Error<E>>
+    /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
+    /// #     D: DelayNs,
+    /// # {
+    /// display.set_brightness(255).await?;
+    /// // Dim to night time brightness over half a second
+    /// display.fade_to(64, 500, delay).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn flush_buffer(&mut self, buf: &[u8]) -> Result<(), Error<E>> {
-        self.write_command(&Command::WriteRam.prepare()?).await?;
-        self.start_data()?;
-        self.spi.write(buf).await.map_err(Error::Comm)
+    pub async fn set_brightness(&mut self, level: u8) -> Result<(), Error<E>> {
+        let (current, master) = brightness_to_contrast(level);
+        self.set_contrast(current, master).await?;
+        self.brightness = level;
+        Ok(())
     }
 
-    /// Flushes the provided frame to the display.
-    ///
-    /// This function writes the contents of the provided frame buffer to the display's RAM.
-    /// It sets the address window to the dimensions of the frame, sends the `WriteRam` command,
-    /// and then writes the frame data to the display.
-    ///
-    /// > **This function is only available when the `frame` feature is enabled.**
+    /// Sets the brightness of the display as a percentage. See `set_brightness`.
     ///
     /// # Parameters
     ///
-    /// - `frame`: A reference to a `Frame` struct containing the data to be written to the display.
+    /// - `percent`: The brightness, 0-100.
     ///
     /// # Returns
     ///
-    /// A `Result` which is `Ok` if the frame is successfully flushed to the display, or an `Error` if the operation fails.
+    /// A `Result` which is `Ok` if the brightness is successfully set, or an `Error` if the operation fails.
     ///
     /// # Errors
     ///
-    /// - `Error::Comm`: If there is a communication error during the SPI write operation.
-    ///
-    /// # Example
+    /// - `Error::CommandError(CommandError::OutOfRange)`: If `percent` is greater than 100.
+    pub async fn set_brightness_percent(&mut self, percent: u8) -> Result<(), Error<E>> {
+        if percent > 100 {
+            return Err(Error::CommandError(CommandError::OutOfRange));
+        }
+        self.set_brightness((percent as u16 * 255 / 100) as u8).await
+    }
+
+    /// Returns the brightness of the display on the 0-255 scale of `set_brightness`.
+    ///
+    /// If the contrast was last set with `set_contrast`, this is the closest level at or below it.
+    pub fn brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// Fades the brightness of the display from its current level to `level`.
+    ///
+    /// The brightness is changed in steps at most every 10ms, spread evenly over `duration_ms`.
+    ///
+    /// # Parameters
+    ///
+    /// - `level`: The brightness to fade to, 0-255.
+    /// - `duration_ms`: How long the fade takes, in milliseconds.
+    /// - `delay`: A mutable reference to an implementation of the `DelayNs` trait, used to pace the fade.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the fade completes, or an `Error` if the operation fails. If a
+    /// step fails, the display is left at the brightness of the previous step.
+    pub async fn fade_to<D>(&mut self, level: u8, duration_ms: u32, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        let start = self.brightness as i32;
+        let change = level as i32 - start;
+        let steps = (duration_ms / FADE_STEP_MS).clamp(1, change.unsigned_abs().max(1));
+        for step in 1..=steps {
+            let elapsed = |step: u32| (duration_ms as u64 * step as u64 / steps as u64) as u32;
+            delay.delay_ms(elapsed(step) - elapsed(step - 1)).await;
+            self.set_brightness((start + change * step as i32 / steps as i32) as u8).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends a command to the SSD1322 display.
+    ///
+    /// This function writes a command to the SSD1322 display using SPI communication. 
+    /// It first sets the data/command pin low to indicate that a command is being sent, 
+    /// writes the command, and if there is any associated data, it sets the data/command 
+    /// pin high and writes the data.
+    ///
+    /// # Parameters
+    ///
+    /// - `command`: A reference to a `CommandData` struct containing the command byte and optional data bytes.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the command is successfully written, or an `Error` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Comm`: If there is a communication error during the SPI write operation.
+    async fn write_command(
+        &mut self,
+        command: &CommandData,
+    ) -> Result<(), Error<E>> {
+        self.write_raw_command(command.cmd, command.args()).await
+    }
+
+    /// Sends an arbitrary command byte followed by an arbitrary number of argument bytes.
+    ///
+    /// This is an escape hatch for vendor-specific, undocumented or otherwise unsupported
+    /// commands (for example unlock sequences required by some modules). Unlike the commands
+    /// sent by the rest of this driver, **no validation is performed**: the command byte is sent
+    /// with the data/command pin low, and `data` (if not empty) is sent with the pin high, exactly
+    /// as given. Prefer the typed methods wherever they cover the command you need.
+    ///
+    /// # Parameters
+    ///
+    /// - `cmd`: The command byte to send.
+    /// - `data`: The argument bytes to send after the command byte. May be empty.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the command is successfully written, or an `Error` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Comm`: If there is a communication error during the SPI write operation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ssd1322_rs::{SSD1322, Error};
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E>(display: &mut SSD1322<SPI, DC, RST, PO>) -> Result<(), Error<E>>
+    /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
+    /// # {
+    /// // Display Enhancement B, as recommended by some module datasheets
+    /// display.write_raw_command(0xD1, &[0x82, 0x20]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write_raw_command(&mut self, cmd: u8, data: &[u8]) -> Result<(), Error<E>> {
+        #[cfg(any(feature = "defmt", feature = "log"))]
+        match Command::decode(cmd, data) {
+            Ok(command) => trace!("command {:?}", command),
+            Err(_) => trace!("raw command {:?} {:?}", cmd, data),
+        }
+
+        let dc = &mut self.dc;
+        dc.set_low().ok();
+
+        self.spi.write(&[cmd]).await.map_err(Error::Comm)?;
+
+        if !data.is_empty() {
+            dc.set_high().ok();
+            self.spi
+                .write(data)
+                .await
+                .map_err(Error::Comm)?;
+        }
+        Ok(())
+    }
+
+    /// Reports time passing to the idle policy (see `Config::idle_policy`), burn-in protection
+    /// (see `Config::burn_in`) and the wear map (see `set_wear_map`, with the `wear` feature).
+    ///
+    /// The idle policy dims the display or puts the panel to sleep once it has gone long enough
+    /// without a flush, burn-in protection shifts or inverts the image, and the wear map
+    /// accumulates the frame shown. Call this periodically, for example from the application's
+    /// main loop. Does nothing if none of them is configured.
+    ///
+    /// # Parameters
+    ///
+    /// - `elapsed_ms`: The time since the previous call, in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the idle policy is applied, or an `Error` if the operation fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ssd1322_rs::{SSD1322, Error};
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E>(display: &mut SSD1322<SPI, DC, RST, PO>, buffer: &[u8], changed: bool) -> Result<(), Error<E>>
+    /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
+    /// # {
+    /// // In the application's main loop, which runs every 100ms
+    /// if changed {
+    ///     // Wakes the display and restores its brightness if it was idle
+    ///     display.flush_buffer(buffer).await?;
+    /// }
+    /// display.tick(100).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn tick(&mut self, elapsed_ms: u32) -> Result<(), Error<E>> {
+        #[cfg(feature = "wear")]
+        self.accumulate_wear(elapsed_ms);
+        self.idle_ms = self.idle_ms.saturating_add(elapsed_ms);
+        if let Some(policy) = self.idle_policy {
+            self.apply_idle_policy(policy).await?;
+        }
+        if let Some(protection) = self.burn_in {
+            self.apply_burn_in(protection, elapsed_ms).await?;
+        }
+        Ok(())
+    }
+
+    // Helper function to dim or sleep the display once it has been idle for long enough
+    async fn apply_idle_policy(&mut self, policy: IdlePolicy) -> Result<(), Error<E>> {
+        if !self.idle_dimmed && policy.dim_after_ms.is_some_and(|ms| self.idle_ms >= ms) {
+            // Compare segment currents, so dimming never undoes the APL limit
+            let (current, master) = brightness_to_contrast(policy.dim_brightness);
+            let lit = self.limited_current.unwrap_or(self.contrast_current) as u16 * (self.master_contrast as u16 + 1);
+            if current as u16 * (master as u16 + 1) < lit {
+                self.write_command(&Command::SetContrastCurrent(self.scan_current(current)).prepare()?).await?;
+                self.write_command(&Command::SetMasterContrast(master).prepare()?).await?;
+            }
+            self.idle_dimmed = true;
+        }
+        if !self.idle_asleep && policy.sleep_after_ms.is_some_and(|ms| self.idle_ms >= ms) {
+            self.write_command(&Command::SetSleepMode(true).prepare()?).await?;
+            self.idle_asleep = true;
+        }
+        Ok(())
+    }
+
+    /// Waits for `ms` milliseconds, then reports the time to the idle policy and burn-in
+    /// protection. See `tick`.
+    ///
+    /// # Parameters
+    ///
+    /// - `delay`: A mutable reference to an implementation of the `DelayNs` trait.
+    /// - `ms`: The time to wait, in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the idle policy is applied, or an `Error` if the operation fails.
+    pub async fn tick_delay<D>(&mut self, delay: &mut D, ms: u32) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        delay.delay_ms(ms).await;
+        self.tick(ms).await
+    }
+
+    /// Returns `true` if the idle policy has dimmed the display or put the panel to sleep.
+    pub fn is_idle(&self) -> bool {
+        self.idle_dimmed || self.idle_asleep
+    }
+
+    // Helper function to undo the idle policy, called whenever a flush has been written
+    async fn wake_from_idle(&mut self) -> Result<(), Error<E>> {
+        self.idle_ms = 0;
+        if self.idle_asleep {
+            self.write_command(&Command::SetSleepMode(false).prepare()?).await?;
+            self.idle_asleep = false;
+        }
+        if self.idle_dimmed {
+            let current = self.scan_current(self.limited_current.unwrap_or(self.contrast_current));
+            self.write_command(&Command::SetContrastCurrent(current).prepare()?).await?;
+            self.write_command(&Command::SetMasterContrast(self.master_contrast).prepare()?).await?;
+            self.idle_dimmed = false;
+        }
+        self.restore_burn_in_inversion().await
+    }
+
+    // Helper function to send the display start line for the page being shown, the scroll
+    // position and the burn-in protection's vertical shift
+    async fn update_start_line(&mut self) -> Result<(), Error<E>> {
+        let mut top = self.front_page as u16 * self.page_height as u16 + self.scroll as u16;
+        // Scanning from `COM[N-1]` to `COM0`, a lower multiplex ratio shows every RAM row
+        // `num_rows - mux` rows further down the panel, which the start line makes up for
+        if !self.row_zero_first() {
+            top += (self.num_rows - self.mux) as u16;
+        }
+        // Showing the RAM from `rows` above the top moves the image down by `rows`
+        let start_line = ((top + 128 - self.shift_rows as u16) % 128) as u8;
+        if start_line != self.start_line {
+            self.write_command(&Command::SetStartLine(start_line).prepare()?).await?;
+            self.start_line = start_line;
+        }
+        Ok(())
+    }
+
+    // Helper function to log the size of a flush at the configured level
+    fn log_flush(&self, len: usize) {
+        match self.flush_log_level {
+            LogLevel::Off => (),
+            LogLevel::Trace => trace!("flush {} bytes", len),
+            LogLevel::Debug => debug!("flush {} bytes", len),
+            LogLevel::Info => info!("flush {} bytes", len),
+        }
+    }
+
+    // Helper function to set the DC pin high ready for Data transmission
+    fn start_data(&mut self) -> Result<(), Error<E>> {
+        // Write command isnt in here because this isnt an async function
+        self.dc.set_high().map_err(Error::Pin)
+    }
+
+    /// Sends data to the SSD1322 display.
+    ///
+    /// This function sends data from a buffer to the screen. It assumes the correct initialisation 
+    /// commands have already been sent, and that the address window has been set/
+    ///
+    /// # Parameters
+    ///
+    /// - `data`: A reference to a buffer containing the pixel data.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the command is successfully written, or an `Error` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Comm`: If there is a communication error during the SPI write operation.
+    pub async fn write_data(&mut self, data: &[u8]) -> Result<(), Error<E>> {
+        self.start_data()?;
+        self.spi.write(data).await.map_err(Error::Comm)
+    }
+
+    /// Sets the address window for the display.
+    ///
+    /// This function defines a rectangular area (address window) on the display where subsequent drawing operations will be applied.
+    /// It calculates the necessary column and row addresses based on the provided dimensions and starting coordinates.
+    ///
+    /// # Parameters
+    ///
+    /// - `start_x`: The starting x-coordinate (horizontal) of the address window.
+    /// - `start_y`: The starting y-coordinate (vertical) of the address window.
+    /// - `width`: The width of the address window.
+    /// - `height`: The height of the address window.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the address window is successfully set, or an `Error` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::CommandError(CommandError::OutOfRange)`: If the calculated column or row addresses are out of the valid range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ssd1322_rs::{SSD1322, Error};
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E>(display: &mut SSD1322<SPI, DC, RST, PO>) -> Result<(), Error<E>>
+    /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
+    /// # {
+    /// display.set_address_window(0, 0, 256, 64).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_address_window(
+        &mut self,
+        start_x: u16,
+        start_y: u16,
+        width: u16,
+        height: u16,
+    ) -> Result<(), Error<E>> {
+        self.set_window(start_x, start_y, width, height, 0).await
+    }
+
+    // Sets the address window, moved right by `column_shift` column addresses
+    async fn set_window(
+        &mut self,
+        start_x: u16,
+        start_y: u16,
+        width: u16,
+        height: u16,
+        column_shift: u16,
+    ) -> Result<(), Error<E>> {
+        let offset = (480-width+start_x) / 2;
+        let column_start = offset / 4 + column_shift;
+        let column_end = (column_start + (width / 4)) - 1;
+
+        if column_start > BUF_COL_MAX.into() || column_end > BUF_COL_MAX.into() ||
+        height == 0 || start_y + height - 1 > PIXEL_ROW_MAX.into() {
+         return Err(Error::CommandError(CommandError::OutOfRange))
+        }
+
+        let row_end = start_y + height - 1;
+        self.write_command(&Command::SetColumnAddress(column_start as u8, column_end as u8).prepare()?).await?;
+        self.write_command(&Command::SetRowAddress(start_y as u8, row_end as u8).prepare()?).await
+    }
+
+    // Writes `height` rows of packed pixels to the display RAM from `ram_row`, wrapping around from
+    // the last row of the display RAM to the first. Fails with `OutOfRange` if `ram_row` is outside
+    // of the display RAM, or the rows don't fit in it.
+    async fn write_ring_rows(
+        &mut self,
+        ram_row: u16,
+        width: u16,
+        height: u16,
+        data: &[u8],
+        column_shift: u16,
+    ) -> Result<(), Error<E>> {
+        let ram_rows = PIXEL_ROW_MAX as u16 + 1;
+        if ram_row >= ram_rows || height > ram_rows {
+            return Err(Error::CommandError(CommandError::OutOfRange));
+        }
+        let first = height.min(ram_rows - ram_row);
+        let split = (first as usize * width as usize / 2).min(data.len());
+
+        self.set_window(0, ram_row, width, first, column_shift).await?;
+        self.write_command(&Command::WriteRam.prepare()?).await?;
+        self.write_data(&data[..split]).await?;
+        if first < height {
+            self.set_window(0, 0, width, height - first, column_shift).await?;
+            self.write_command(&Command::WriteRam.prepare()?).await?;
+            self.write_data(&data[split..]).await?;
+        }
+        Ok(())
+    }
+
+    /// Scrolls the display so that row `row` of the display RAM is shown at the top of the panel.
+    ///
+    /// The display RAM has 128 rows, more than most panels show, and is used as a ring buffer: the
+    /// rows below the bottom of the panel wrap around to the top of the display RAM. Scrolling
+    /// only moves the display start line, so it costs a single command however far it scrolls.
+    /// Use `write_rows` to fill in the rows scrolled into view.
+    ///
+    /// # Parameters
+    ///
+    /// - `row`: The row of the display RAM to show at the top of the panel, 0-127.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the display is successfully scrolled, or an `Error` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::CommandError(CommandError::OutOfRange)`: If `row` is greater than 127, or
+    ///   `Config::page_flipping` is enabled, which uses the start line to switch pages instead.
+    pub async fn scroll_to(&mut self, row: u8) -> Result<(), Error<E>> {
+        if row > PIXEL_ROW_MAX || self.page_flipping {
+            return Err(Error::CommandError(CommandError::OutOfRange));
+        }
+        self.scroll = row;
+        self.update_start_line().await
+    }
+
+    /// Scrolls the display by `rows`: positive values move the content up, revealing the rows of
+    /// the display RAM below the panel, and negative values move it down. See `scroll_to`.
+    ///
+    /// # Parameters
+    ///
+    /// - `rows`: The number of rows to scroll by. Scrolling wraps around the 128 rows of the
+    ///   display RAM.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the display is successfully scrolled, or an `Error` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::CommandError(CommandError::OutOfRange)`: If `Config::page_flipping` is enabled.
+    ///
+    /// # Example
+    ///
+    /// A log viewer on a 256x64 panel, adding an 8 row line of text at the bottom:
+    ///
+    /// ```
+    /// # use ssd1322_rs::{SSD1322, Error};
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E>(display: &mut SSD1322<SPI, DC, RST, PO>, line: &[u8; 256 * 8 / 2]) -> Result<(), Error<E>>
+    /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
+    /// # {
+    /// // Write the line just below the bottom of the panel, then scroll it into view
+    /// display.write_rows(64, 256, line).await?;
+    /// for _ in 0..8 {
+    ///     display.scroll_by(1).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn scroll_by(&mut self, rows: i16) -> Result<(), Error<E>> {
+        let row = (self.scroll as i32 + rows as i32).rem_euclid(PIXEL_ROW_MAX as i32 + 1);
+        self.scroll_to(row as u8).await
+    }
+
+    /// Returns the row of the display RAM shown at the top of the panel. See `scroll_to`.
+    pub fn scroll_position(&self) -> u8 {
+        self.scroll
+    }
+
+    /// Writes rows of packed pixels relative to the top of the panel, as currently scrolled.
+    ///
+    /// Rows past the bottom of the panel are written to the part of the display RAM that will be
+    /// scrolled into view by `scroll_by` with positive values, wrapping around the 128 rows of the
+    /// display RAM.
+    ///
+    /// # Parameters
+    ///
+    /// - `y`: The row to start writing at, relative to the top of the panel, 0-127.
+    /// - `width`: The width of the rows in pixels, as for `set_address_window`.
+    /// - `data`: The packed 4bpp pixels, in the same format as a `Frame` buffer. Its length must
+    ///   be a whole number of rows, at most 128.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the rows are successfully written, or an `Error` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::CommandError(CommandError::OutOfRange)`: If `y` is greater than 127, `width` is
+    ///   0, greater than 480 or not a multiple of 4, or `data` is empty, not a whole number of
+    ///   rows, or more than 128 rows.
+    pub async fn write_rows(&mut self, y: u16, width: u16, data: &[u8]) -> Result<(), Error<E>> {
+        let row_len = width as usize / 2;
+        let ram_rows = PIXEL_ROW_MAX as u16 + 1;
+        if y >= ram_rows || width % 4 != 0 || width > NUM_PIXEL_COLS || row_len == 0 || data.is_empty()
+            || data.len() % row_len != 0
+            || data.len() / row_len > ram_rows as usize
+        {
+            return Err(Error::CommandError(CommandError::OutOfRange));
+        }
+        let height = (data.len() / row_len) as u16;
+        let ram_row = (self.scroll as u16 + y) % ram_rows;
+        let shift = self.shift_columns[0];
+        self.write_ring_rows(ram_row, width, height, data, shift as u16).await
+    }
+
+    /// Flushes the provided buffer to the display.
+    ///
+    /// This function writes the contents of the provided buffer to the display's RAM.
+    /// It sends the `WriteRam` command to the display, followed by the buffer data.
+    ///
+    /// # Parameters
+    ///
+    /// - `buf`: A slice containing the data to be written to the display.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the buffer is successfully flushed to the display, or an `Error` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Comm`: If there is a communication error during the SPI write operation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ssd1322_rs::{SSD1322, Error};
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E>(display: &mut SSD1322<SPI, DC, RST, PO>, buffer: &[u8]) -> Result<(), Error<E>>
+    /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
+    /// # {
+    /// display.flush_buffer(buffer).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn flush_buffer(&mut self, buf: &[u8]) -> Result<(), Error<E>> {
+        self.log_flush(buf.len());
+        self.write_command(&Command::WriteRam.prepare()?).await?;
+        self.start_data()?;
+        self.spi.write(buf).await.map_err(Error::Comm)?;
+        // Only wake once the new image is in the RAM, so the old one isn't shown again meanwhile
+        self.wake_from_idle().await
+    }
+
+    /// Flushes the provided frame to the display.
+    ///
+    /// This function writes the contents of the provided frame buffer to the display's RAM.
+    /// It sets the address window to the dimensions of the frame, sends the `WriteRam` command,
+    /// and then writes the frame data to the display.
+    ///
+    /// If `Config::page_flipping` is enabled, the frame is written to the half of the display RAM
+    /// that isn't being shown, and then shown by moving the display start line.
+    ///
+    /// If `Config::apl_limit` is set and the frame's average picture level exceeds it, the
+    /// contrast current is lowered in proportion before the frame is written. It is restored once
+    /// a frame within the limit is flushed.
+    ///
+    /// > **This function is only available when the `frame` feature is enabled.**
+    ///
+    /// # Parameters
+    ///
+    /// - `frame`: A reference to a `Frame` struct containing the data to be written to the display.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` which is `Ok` if the frame is successfully flushed to the display, or an `Error` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Comm`: If there is a communication error during the SPI write operation.
+    /// - `Error::CommandError(CommandError::OutOfRange)`: If the frame is more than 128 rows high,
+    ///   or `Config::page_flipping` is enabled and the frame is more than 64 rows high, so two
+    ///   pages don't fit in the display RAM.
+    ///
+    /// # Example
     ///
     /// ```
     /// # use ssd1322_rs::{SSD1322, Frame, Error};
-    /// # async fn example_usage<const N: usize>(display: &mut SSD1322<SPI, DC, RST, PO>, frame: &Frame<N>) -> Result<(), Error<E>> {
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E, const N: usize>(display: &mut SSD1322<SPI, DC, RST, PO>, frame: &Frame<N>) -> Result<(), Error<E>>
+    /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
+    /// # {
     /// display.flush_frame(frame).await?;
     /// # Ok(())
     /// # }
     /// ```
     #[cfg(feature = "frame")]
     pub async fn flush_frame<const N: usize>(&mut self, frame: &Frame<N>) -> Result<(), Error<E>> {
-        self.set_address_window(0, 0, frame.width, frame.height)
+        if self.page_flipping && frame.height.saturating_mul(2) > PIXEL_ROW_MAX as u16 + 1 {
+            return Err(Error::CommandError(CommandError::OutOfRange));
+        }
+        self.log_flush(frame.buffer.len());
+
+        // Lower the current before a brighter frame is shown, and raise it after a darker one is.
+        // While dimmed, the current is restored with the limit applied when the display wakes.
+        let current = self.limited_current.unwrap_or(self.contrast_current);
+        if self.apl_limit.is_some() {
+            self.frame_apl = frame.stats().apl();
+        }
+        let limited = self.apl_limited_current(self.contrast_current);
+        let dimmed = self.idle_dimmed;
+        if dimmed {
+            self.limited_current = (limited != self.contrast_current).then_some(limited);
+        } else if limited < current {
+            self.apply_limited_current(limited).await?;
+        }
+
+        // With page flipping, write to the page that isn't being shown. Otherwise write at the
+        // top of the panel, wherever it has been scrolled to.
+        let (page, start_y, rows) = if self.page_flipping {
+            let page = self.front_page ^ 1;
+            (page, page as u16 * frame.height, frame.height)
+        } else {
+            (0, self.scroll as u16, PIXEL_ROW_MAX as u16 + 1)
+        };
+        let shift = self.pending_shift_columns.min(Self::spare_shift_columns(frame.width));
+        self.write_ring_rows(start_y, frame.width, frame.height, &frame.buffer, shift as u16)
             .await?;
-        self.write_command(&Command::WriteRam.prepare()?).await?;
-        self.write_data(&frame.buffer).await
+        if shift != self.shift_columns[page as usize] {
+            let clear_from = if self.page_flipping { start_y } else { 0 };
+            self.clear_uncovered_columns(frame.width, clear_from, rows, page, shift).await?;
+        }
+        if self.page_flipping {
+            self.front_page = page;
+            self.page_height = frame.height as u8;
+            self.update_start_line().await?;
+        }
+        #[cfg(feature = "wear")]
+        self.record_wear(frame);
+
+        // Only wake once the new frame is in the RAM, so the old one isn't shown again meanwhile
+        self.wake_from_idle().await?;
+        if !dimmed && limited > current {
+            self.apply_limited_current(limited).await?;
+        }
+        Ok(())
+    }
+
+    // Helper function to send the contrast current chosen by the APL limiter
+    #[cfg(feature = "frame")]
+    async fn apply_limited_current(&mut self, current: u8) -> Result<(), Error<E>> {
+        debug!("APL limiter contrast current {}", current);
+        self.write_command(&Command::SetContrastCurrent(self.scan_current(current)).prepare()?).await?;
+        self.limited_current = (current != self.contrast_current).then_some(current);
+        Ok(())
     }
 }
 
 /// Error Types used within this crate
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error<E = ()> {
     /// Communication error
     Comm(E),
@@ -519,7 +1792,6 @@ impl<E> From<CommandError> for Error<E> {
 ///
 /// This module provides the `Frame` struct, which represents a frame buffer for the SSD1322 display.
 /// It includes functionality for creating, manipulating, and drawing to the frame buffer using the `embedded-graphics` traits.
-
 #[cfg(feature = "frame")]
 mod frame {
     extern crate embedded_graphics_core;
@@ -530,7 +1802,17 @@ mod frame {
             Gray4,
         },
         prelude::*,
+        primitives::Rectangle,
     };
+    use core::fmt;
+    use crate::instruction::NibbleRemap;
+
+    /// Characters used to render the 16 grey levels as text, from level 0 (off) to level 15.
+    pub const ASCII_RAMP: &[u8; 16] = b" .,:;-~=+*ox#%&@";
+
+    /// The widest region, in pixels, that `AsciiArt` renders over `defmt`.
+    #[cfg(feature = "defmt")]
+    const DEFMT_MAX_WIDTH: usize = 480;
 
 
     /// A frame buffer for the SSD1322 display.
@@ -544,6 +1826,28 @@ mod frame {
         pub height: u16,
         /// The buffer storing the pixel data.
         pub buffer: [u8; N],
+        /// The order of the 4 pixels packed into each pair of bytes.
+        nibble_order: NibbleRemap,
+        /// The rotation applied to drawing coordinates.
+        rotation: Rotation,
+    }
+
+    /// Rotation of the drawing coordinates of a `Frame` relative to the panel, clockwise.
+    ///
+    /// With `Deg90` and `Deg270`, drawing code sees a surface with the width and height of the
+    /// panel swapped, for example 64x256 on a 256x64 panel mounted vertically.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub enum Rotation {
+        /// No rotation.
+        #[default]
+        Deg0,
+        /// Rotated 90° clockwise: the top of the drawing is at the right of the panel.
+        Deg90,
+        /// Rotated 180°.
+        Deg180,
+        /// Rotated 270° clockwise: the top of the drawing is at the left of the panel.
+        Deg270,
     }
 
     impl<const N: usize> Frame<N> {
@@ -564,9 +1868,102 @@ mod frame {
                 width,
                 height,
                 buffer,
+                nibble_order: NibbleRemap::Forward,
+                rotation: Rotation::Deg0,
             }
         }
 
+        /// Sets the rotation of the drawing coordinates, for panels mounted sideways or upside
+        /// down. `set_pixel`, `get_pixel`, the `DrawTarget` implementation and the text renderings
+        /// all use the rotated coordinates, while `width`, `height`, the buffer, `stats` and the
+        /// snapshots stay those of the panel, so `flush_frame` is unchanged. The buffer is not
+        /// redrawn: clear the frame and draw it again after changing the rotation.
+        ///
+        /// # Parameters
+        ///
+        /// - `rotation`: The rotation to apply.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// # use embedded_graphics_core::pixelcolor::{Gray4, GrayColor};
+        /// # use embedded_graphics_core::geometry::{OriginDimensions, Size};
+        /// # use ssd1322_rs::{Frame, Rotation};
+        /// let mut frame = Frame::new(256, 64, [0; 8192]);
+        /// frame.set_rotation(Rotation::Deg90);
+        /// assert_eq!(frame.size(), Size::new(64, 256));
+        ///
+        /// // The top left of the drawing is at the top right of the panel
+        /// frame.set_pixel(0, 0, Gray4::WHITE);
+        /// assert_eq!(frame.buffer[127], 0x0F);
+        /// ```
+        pub fn set_rotation(&mut self, rotation: Rotation) {
+            self.rotation = rotation;
+        }
+
+        /// Returns the rotation of the drawing coordinates. See `set_rotation`.
+        pub fn rotation(&self) -> Rotation {
+            self.rotation
+        }
+
+        /// Maps drawing coordinates to panel coordinates, or returns `None` if they are outside of
+        /// the frame.
+        fn to_panel(&self, x: u16, y: u16) -> Option<(u16, u16)> {
+            let Size { width, height } = self.size();
+            if x as u32 >= width || y as u32 >= height {
+                return None;
+            }
+            let (w, h) = (self.width, self.height);
+            Some(match self.rotation {
+                Rotation::Deg0 => (x, y),
+                Rotation::Deg90 => (w - 1 - y, x),
+                Rotation::Deg180 => (w - 1 - x, h - 1 - y),
+                Rotation::Deg270 => (y, h - 1 - x),
+            })
+        }
+
+        /// Sets the 4-bit grey level of a pixel at drawing coordinates, ignoring pixels outside
+        /// of the frame.
+        fn draw_level(&mut self, x: u16, y: u16, level: u8) {
+            if let Some((x, y)) = self.to_panel(x, y) {
+                self.set_level(x, y, level);
+            }
+        }
+
+        /// Returns the 4-bit grey level of a pixel at drawing coordinates, or `None` if the pixel
+        /// is outside of the frame.
+        fn drawn_level(&self, x: u16, y: u16) -> Option<u8> {
+            let (x, y) = self.to_panel(x, y)?;
+            self.level(x, y)
+        }
+
+        /// Sets the order the pixels are packed into the buffer, which must match
+        /// `Config::nibble_order`. The buffer is repacked, so the image is unchanged.
+        ///
+        /// The buffer holds 4 pixels in each pair of bytes, as 4 nibbles. With
+        /// `NibbleRemap::Forward` (the default) the pixels are in order from the high nibble of
+        /// the first byte; with `NibbleRemap::Reverse` they are in order from the low nibble of
+        /// the second byte. The width of the frame must be a multiple of 4.
+        ///
+        /// # Parameters
+        ///
+        /// - `order`: The order to pack pixels in.
+        pub fn set_nibble_order(&mut self, order: NibbleRemap) {
+            if order != self.nibble_order {
+                for pair in self.buffer.chunks_exact_mut(2) {
+                    let (first, second) = (pair[0], pair[1]);
+                    pair[0] = second.rotate_left(4);
+                    pair[1] = first.rotate_left(4);
+                }
+                self.nibble_order = order;
+            }
+        }
+
+        /// Returns the order the pixels are packed into the buffer. See `set_nibble_order`.
+        pub fn nibble_order(&self) -> NibbleRemap {
+            self.nibble_order
+        }
+
         /// Sets a pixel in the frame buffer to the specified color.
         ///
         /// # Parameters
@@ -574,25 +1971,64 @@ mod frame {
         /// - `x`: The x-coordinate of the pixel.
         /// - `y`: The y-coordinate of the pixel.
         /// - `color`: The greyscale colour to set the pixel to.
-        pub fn set_pixel(&mut self, x: u8, y: u8, color: Gray4) {
-            let color = RawU4::from(color).into_inner();
-            if x as usize >= self.width as usize || y as usize >= self.height as usize {
-                return;
+        pub fn set_pixel(&mut self, x: u16, y: u16, color: Gray4) {
+            self.draw_level(x, y, RawU4::from(color).into_inner());
+        }
+
+        /// Returns the colour of a pixel in the frame buffer, or `None` if the pixel is outside of
+        /// the frame.
+        ///
+        /// # Parameters
+        ///
+        /// - `x`: The x-coordinate of the pixel.
+        /// - `y`: The y-coordinate of the pixel.
+        pub fn get_pixel(&self, x: u16, y: u16) -> Option<Gray4> {
+            self.drawn_level(x, y).map(Gray4::new)
+        }
+
+        /// Returns the index of the byte holding a pixel, and whether the pixel is stored in the
+        /// higher 4 bits of that byte.
+        fn locate(&self, x: u16, y: u16) -> Option<(usize, bool)> {
+            if x >= self.width || y >= self.height {
+                return None;
             }
-            let idx = ((y as usize) * self.width as usize + (x as usize)) / 2;
+            let pixel = (y as usize) * self.width as usize + (x as usize);
+            // Each pair of bytes holds 4 pixels, one per nibble
+            let nibble = match self.nibble_order {
+                NibbleRemap::Forward => pixel % 4,
+                NibbleRemap::Reverse => 3 - pixel % 4,
+            };
+            let idx = (pixel - pixel % 4 + nibble) / 2;
             if idx >= self.buffer.len() {
-                return;
+                return None;
             }
+            // Even nibbles are stored in the higher 4 bits
+            Some((idx, nibble % 2 == 0))
+        }
+
+        /// Sets the 4-bit grey level of a pixel, ignoring pixels outside of the frame.
+        pub(crate) fn set_level(&mut self, x: u16, y: u16, level: u8) {
+            let Some((idx, high)) = self.locate(x, y) else {
+                return;
+            };
 
             // Extract the 4-bit color value
-            let color = color & 0x0F;
+            let level = level & 0x0F;
+
+            if high {
+                self.buffer[idx] = (self.buffer[idx] & 0x0F) | (level << 4);
+            } else {
+                self.buffer[idx] = (self.buffer[idx] & 0xF0) | level;
+            }
+        }
 
-            if x % 2 == 0 {
-                // Set the higher 4 bits for even x coordinate
-                self.buffer[idx] = (self.buffer[idx] & 0x0F) | (color << 4);
+        /// Returns the 4-bit grey level of a pixel, or `None` if the pixel is outside of the frame.
+        pub(crate) fn level(&self, x: u16, y: u16) -> Option<u8> {
+            let (idx, high) = self.locate(x, y)?;
+            if high {
+                Some(self.buffer[idx] >> 4)
             } else {
-                // Set the lower 4 bits for odd x coordinate
-                self.buffer[idx] = (self.buffer[idx] & 0xF0) | color;
+                Some(self.buffer[idx] & 0x0F)
             }
         }
     }
@@ -602,6 +2038,8 @@ mod frame {
                 width: 256,
                 height: 64,
                 buffer: [0; N],
+                nibble_order: NibbleRemap::Forward,
+                rotation: Rotation::Deg0,
             }
         }
     }
@@ -618,7 +2056,9 @@ mod frame {
             pixels
                 .into_iter()
                 .filter(|Pixel(pos, _color)| bb.contains(*pos))
-                .for_each(|Pixel(pos, color)| self.set_pixel(pos.x as u8, pos.y as u8, color));
+                .for_each(|Pixel(pos, color)| {
+                    self.draw_level(pos.x as u16, pos.y as u16, RawU4::from(color).into_inner())
+                });
             Ok(())
         }
 
@@ -632,14 +2072,487 @@ mod frame {
         }
     }
 
+    /// The size of the drawing surface, which has the width and height of the frame swapped when
+    /// it is rotated by 90° or 270°.
     impl<const N: usize> OriginDimensions for Frame<N> {
         fn size(&self) -> Size {
-            Size::new(self.width as u32, self.height as u32)
+            match self.rotation {
+                Rotation::Deg0 | Rotation::Deg180 => Size::new(self.width as u32, self.height as u32),
+                Rotation::Deg90 | Rotation::Deg270 => Size::new(self.height as u32, self.width as u32),
+            }
+        }
+    }
+
+    impl<const N: usize> Frame<N> {
+        /// Returns a text rendering of the whole frame, one character per pixel using
+        /// `ASCII_RAMP`. The result implements `Display` (and `defmt::Format` when the `defmt`
+        /// feature is enabled), which makes it possible to dump what the firmware drew.
+        pub fn ascii(&self) -> AsciiArt<'_, N> {
+            self.ascii_region(self.bounding_box())
+        }
+
+        /// Returns a text rendering of a region of the frame, one character per pixel using
+        /// `ASCII_RAMP`. The region is clipped to the frame.
+        ///
+        /// # Parameters
+        ///
+        /// - `area`: The region of the frame to render.
+        pub fn ascii_region(&self, area: Rectangle) -> AsciiArt<'_, N> {
+            AsciiArt {
+                frame: self,
+                area: area.intersection(&self.bounding_box()),
+            }
+        }
+    }
+
+    /// A text rendering of a region of a `Frame`, created with `Frame::ascii` or
+    /// `Frame::ascii_region`.
+    ///
+    /// Each pixel is rendered as one character from `ASCII_RAMP`, and each row of pixels as one
+    /// line of text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use embedded_graphics_core::pixelcolor::{Gray4, GrayColor};
+    /// # use ssd1322_rs::Frame;
+    /// let mut frame = Frame::new(4, 2, [0; 4]);
+    /// frame.set_pixel(1, 0, Gray4::WHITE);
+    /// frame.set_pixel(2, 1, Gray4::new(8));
+    /// assert_eq!(frame.ascii().to_string(), " @  \n  + \n");
+    /// ```
+    pub struct AsciiArt<'a, const N: usize> {
+        frame: &'a Frame<N>,
+        area: Rectangle,
+    }
+
+    impl<const N: usize> AsciiArt<'_, N> {
+        /// Returns the characters of each row of the region.
+        fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = char> + '_> + '_ {
+            let (x0, y0) = (self.area.top_left.x as u16, self.area.top_left.y as u16);
+            let (width, height) = (self.area.size.width as u16, self.area.size.height as u16);
+            (y0..y0 + height).map(move |y| {
+                (x0..x0 + width)
+                    .map(move |x| ASCII_RAMP[self.frame.drawn_level(x, y).unwrap_or(0) as usize] as char)
+            })
+        }
+    }
+
+    impl<const N: usize> fmt::Display for AsciiArt<'_, N> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for row in self.rows() {
+                for c in row {
+                    fmt::Write::write_char(f, c)?;
+                }
+                fmt::Write::write_char(f, '\n')?;
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "defmt")]
+    impl<const N: usize> defmt::Format for AsciiArt<'_, N> {
+        fn format(&self, f: defmt::Formatter<'_>) {
+            let mut line = [0u8; DEFMT_MAX_WIDTH];
+            for row in self.rows() {
+                let mut len = 0;
+                for c in row.take(DEFMT_MAX_WIDTH) {
+                    line[len] = c as u8;
+                    len += 1;
+                }
+                // The ramp is plain ASCII, so this never fails
+                let line = core::str::from_utf8(&line[..len]).unwrap_or_default();
+                defmt::write!(f, "{=str}\n", line);
+            }
+        }
+    }
+
+    /// Statistics of the grey levels in a `Frame`, returned by `Frame::stats`.
+    ///
+    /// An OLED's supply current is roughly proportional to the total luminance of the image it
+    /// shows, so these can be used to estimate, and limit, the current a frame will draw.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    pub struct FrameStats {
+        /// The number of pixels in the frame.
+        pub pixels: u32,
+        /// The sum of the grey levels of every pixel.
+        pub total: u32,
+        /// The number of pixels at each grey level.
+        pub histogram: [u32; 16],
+        /// The highest grey level of any pixel.
+        pub peak: u8,
+    }
+
+    impl FrameStats {
+        /// Returns the average picture level: the average grey level of the frame, scaled so that
+        /// an all black frame is 0 and an all white frame is 255.
+        pub fn apl(&self) -> u8 {
+            if self.pixels == 0 {
+                return 0;
+            }
+            (self.total as u64 * 255 / (self.pixels as u64 * 15)) as u8
+        }
+    }
+
+    impl<const N: usize> Frame<N> {
+        /// Computes statistics of the grey levels in the frame, in a single pass over the buffer.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// # use embedded_graphics_core::pixelcolor::{Gray4, GrayColor};
+        /// # use ssd1322_rs::Frame;
+        /// let mut frame = Frame::new(4, 2, [0; 4]);
+        /// frame.set_pixel(1, 0, Gray4::WHITE);
+        /// frame.set_pixel(2, 1, Gray4::new(5));
+        ///
+        /// let stats = frame.stats();
+        /// assert_eq!(stats.total, 20);
+        /// assert_eq!(stats.peak, 15);
+        /// assert_eq!(stats.histogram[0], 6);
+        /// assert_eq!(stats.apl(), 42);
+        /// ```
+        pub fn stats(&self) -> FrameStats {
+            let pixels = (self.width as usize * self.height as usize).min(self.buffer.len() * 2);
+            let mut stats = FrameStats {
+                pixels: pixels as u32,
+                total: 0,
+                histogram: [0; 16],
+                peak: 0,
+            };
+            let mut count = |level: u8| {
+                stats.total += level as u32;
+                stats.histogram[level as usize] += 1;
+                stats.peak = stats.peak.max(level);
+            };
+            for byte in &self.buffer[..pixels / 2] {
+                count(byte >> 4);
+                count(byte & 0x0F);
+            }
+            if pixels % 2 == 1 {
+                count(self.buffer[pixels / 2] >> 4);
+            }
+            stats
+        }
+    }
+
+    /// Formats the frame's dimensions. The alternate form (`{:#?}`) also renders the frame's
+    /// contents as text, as `Frame::ascii` does.
+    impl<const N: usize> fmt::Debug for Frame<N> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("Frame")
+                .field("width", &self.width)
+                .field("height", &self.height)
+                .finish()?;
+            if f.alternate() {
+                write!(f, "\n{}", self.ascii())?;
+            }
+            Ok(())
         }
     }
 
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const ROTATIONS: [Rotation; 4] = [Rotation::Deg0, Rotation::Deg90, Rotation::Deg180, Rotation::Deg270];
+
+        #[test]
+        fn rotation_maps_the_drawing_corners_to_the_panel() {
+            let mut frame = Frame::new(8, 4, [0; 16]);
+            // The top left, top right and bottom left of the drawing, for each rotation
+            let corners = [
+                [(0, 0), (7, 0), (0, 3)],
+                [(7, 0), (7, 3), (0, 0)],
+                [(7, 3), (0, 3), (7, 0)],
+                [(0, 3), (0, 0), (7, 3)],
+            ];
+            for (rotation, corners) in ROTATIONS.into_iter().zip(corners) {
+                frame.set_rotation(rotation);
+                let Size { width, height } = frame.size();
+                let (right, bottom) = (width as u16 - 1, height as u16 - 1);
+                let mapped = [frame.to_panel(0, 0), frame.to_panel(right, 0), frame.to_panel(0, bottom)];
+                assert_eq!(mapped, corners.map(Some), "{rotation:?}");
+                assert_eq!(frame.to_panel(right + 1, 0), None);
+                assert_eq!(frame.to_panel(0, bottom + 1), None);
+            }
+        }
+
+        #[test]
+        fn quarter_turns_swap_the_size() {
+            let mut frame = Frame::new(8, 4, [0; 16]);
+            for (rotation, size) in ROTATIONS.into_iter().zip([(8, 4), (4, 8), (8, 4), (4, 8)]) {
+                frame.set_rotation(rotation);
+                assert_eq!(frame.size(), Size::new(size.0, size.1));
+                assert_eq!(frame.bounding_box(), Rectangle::new(Point::zero(), frame.size()));
+            }
+        }
+
+        #[test]
+        fn pixels_are_drawn_where_the_rotation_puts_them() {
+            for (rotation, panel) in ROTATIONS.into_iter().zip([(1, 2), (5, 1), (6, 1), (2, 2)]) {
+                let mut frame = Frame::new(8, 4, [0; 16]);
+                frame.set_rotation(rotation);
+                frame.set_pixel(1, 2, Gray4::new(9));
+                assert_eq!(frame.level(panel.0, panel.1), Some(9), "{rotation:?}");
+                assert_eq!(frame.get_pixel(1, 2), Some(Gray4::new(9)));
+                assert_eq!(frame.stats().total, 9);
+            }
+        }
+
+        #[test]
+        fn draw_iter_clips_to_the_rotated_frame() {
+            let mut frame = Frame::new(8, 4, [0; 16]);
+            frame.set_rotation(Rotation::Deg90);
+            // Only (3, 7) is inside the 4x8 drawing; the others are inside the 8x4 panel or
+            // outside both
+            let pixels = [(3, 7), (4, 0), (7, 3), (0, 8), (-1, 0)]
+                .map(|(x, y)| Pixel(Point::new(x, y), Gray4::WHITE));
+            frame.draw_iter(pixels).unwrap();
+            assert_eq!(frame.stats().total, 15);
+            assert_eq!(frame.level(0, 3), Some(15));
+        }
+    }
 }
 
 
 #[cfg(feature = "frame")]
-pub use frame::*;
\ No newline at end of file
+pub use frame::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The segment current the registers drive, in units of the smallest step
+    fn segment_current((current, master): (u8, u8)) -> u32 {
+        current as u32 * (master as u32 + 1)
+    }
+
+    #[test]
+    fn brightness_endpoints_are_off_and_full_current() {
+        assert_eq!(brightness_to_contrast(0), (0, 0));
+        assert_eq!(brightness_to_contrast(255), (255, 15));
+        assert_eq!(contrast_to_brightness(0, 0), 0);
+        assert_eq!(contrast_to_brightness(255, 15), 255);
+    }
+
+    #[test]
+    fn brightness_never_lowers_the_current() {
+        for level in 1..=255 {
+            assert!(
+                segment_current(brightness_to_contrast(level)) >= segment_current(brightness_to_contrast(level - 1)),
+                "level {}",
+                level
+            );
+        }
+        for master in 0..=15 {
+            for current in 1..=255 {
+                assert!(contrast_to_brightness(current, master) >= contrast_to_brightness(current - 1, master));
+            }
+        }
+    }
+
+    #[test]
+    fn brightness_round_trips_through_the_registers() {
+        for level in 0..=255 {
+            let registers = brightness_to_contrast(level);
+            let recovered = contrast_to_brightness(registers.0, registers.1);
+            assert!(recovered <= level, "level {}", level);
+            assert_eq!(brightness_to_contrast(recovered), registers, "level {}", level);
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    mod driver {
+        use super::*;
+        use crate::testing::{block_on, Recorder, Transfer};
+        use std::vec;
+        use std::vec::Vec;
+
+        #[test]
+        fn fade_to_steps_evenly_over_the_duration() {
+            let recorder = Recorder::new();
+            let mut display = recorder.display(Config::default());
+            block_on(display.init_default(&mut recorder.delay())).unwrap();
+            block_on(display.set_brightness(100)).unwrap();
+            recorder.clear();
+
+            block_on(display.fade_to(108, 45, &mut recorder.delay())).unwrap();
+            // 4 steps of 2 levels, spread over 45 ms
+            assert_eq!(recorder.delays(), [11_000_000, 11_000_000, 11_000_000, 12_000_000]);
+            let expected: Vec<_> = [102, 104, 106, 108]
+                .into_iter()
+                .flat_map(|level| {
+                    let (current, master) = brightness_to_contrast(level);
+                    [
+                        Transfer::Command(Command::SetContrastCurrent(current)),
+                        Transfer::Command(Command::SetMasterContrast(master)),
+                    ]
+                })
+                .collect();
+            assert_eq!(recorder.transfers(), expected);
+            assert_eq!(display.brightness(), 108);
+        }
+
+        #[test]
+        fn fade_to_the_current_brightness_takes_one_step() {
+            let recorder = Recorder::new();
+            let mut display = recorder.display(Config::default());
+            block_on(display.set_brightness(50)).unwrap();
+            recorder.clear();
+
+            block_on(display.fade_to(50, 1000, &mut recorder.delay())).unwrap();
+            assert_eq!(recorder.delays(), [1_000_000_000]);
+            assert_eq!(recorder.commands().len(), 2);
+        }
+
+        #[cfg(feature = "frame")]
+        #[test]
+        fn contrast_changes_keep_the_apl_limit() {
+            let recorder = Recorder::new();
+            let mut display = recorder.display(Config { apl_limit: Some(128), ..Config::default() });
+            block_on(display.init_default(&mut recorder.delay())).unwrap();
+            block_on(display.flush_frame(&Frame::new(4, 2, [0xFF; 4]))).unwrap();
+            recorder.clear();
+
+            block_on(display.set_contrast(200, 15)).unwrap();
+            assert_eq!(
+                recorder.transfers(),
+                [
+                    Transfer::Command(Command::SetContrastCurrent(100)),
+                    Transfer::Command(Command::SetMasterContrast(15)),
+                ]
+            );
+            assert_eq!(display.contrast(), (200, 15));
+
+            // A frame within the limit lifts it again
+            block_on(display.flush_frame(&Frame::new(4, 2, [0; 4]))).unwrap();
+            recorder.clear();
+            block_on(display.set_contrast(100, 15)).unwrap();
+            assert_eq!(recorder.commands()[0], Command::SetContrastCurrent(100));
+        }
+
+        #[test]
+        fn flush_wakes_the_display_after_writing_the_frame() {
+            let recorder = Recorder::new();
+            let idle_policy = IdlePolicy { dim_after_ms: Some(10), dim_brightness: 0, sleep_after_ms: Some(20) };
+            let mut display = recorder.display(Config { idle_policy: Some(idle_policy), ..Config::default() });
+            block_on(display.init_default(&mut recorder.delay())).unwrap();
+            block_on(display.tick(30)).unwrap();
+            assert!(display.is_idle());
+
+            recorder.clear();
+            block_on(display.flush_buffer(&[0xFF; 16])).unwrap();
+            let transfers = recorder.transfers();
+            let position = |wanted: fn(&Transfer) -> bool| transfers.iter().position(wanted).unwrap();
+            let data = position(|transfer| matches!(transfer, Transfer::Data(_)));
+            assert!(data < position(|transfer| *transfer == Transfer::Command(Command::SetSleepMode(false))));
+            assert!(data < position(|transfer| matches!(transfer, Transfer::Command(Command::SetMasterContrast(_)))));
+            assert!(!display.is_idle());
+        }
+
+        #[cfg(feature = "frame")]
+        #[test]
+        fn dimming_never_raises_the_apl_limited_current() {
+            // Brightness 200 is above the limited current, so the display stays as it is, while
+            // dimming to 100 goes ahead
+            for (dim_brightness, dims) in [(200, false), (100, true)] {
+                let recorder = Recorder::new();
+                let idle_policy = IdlePolicy { dim_after_ms: Some(10), dim_brightness, sleep_after_ms: None };
+                let config = Config { apl_limit: Some(128), idle_policy: Some(idle_policy), ..Config::default() };
+                let mut display = recorder.display(config);
+                block_on(display.init_default(&mut recorder.delay())).unwrap();
+                block_on(display.set_brightness(255)).unwrap();
+                block_on(display.flush_frame(&Frame::new(4, 2, [0xFF; 4]))).unwrap();
+                recorder.clear();
+
+                block_on(display.tick(10)).unwrap();
+                let (current, master) = brightness_to_contrast(dim_brightness);
+                let expected = [
+                    Transfer::Command(Command::SetContrastCurrent(current)),
+                    Transfer::Command(Command::SetMasterContrast(master)),
+                ];
+                assert_eq!(recorder.transfers(), if dims { &expected[..] } else { &[] });
+            }
+        }
+
+        #[cfg(feature = "frame")]
+        #[test]
+        fn page_flipping_rejects_frames_taller_than_a_page() {
+            let recorder = Recorder::new();
+            let mut display = recorder.display(Config { page_flipping: true, ..Config::default() });
+            block_on(display.init_default(&mut recorder.delay())).unwrap();
+            recorder.clear();
+
+            let frame = Frame::new(256, 65, [0; 256 * 65 / 2]);
+            let result = block_on(display.flush_frame(&frame));
+            assert!(matches!(result, Err(Error::CommandError(CommandError::OutOfRange))));
+            assert!(recorder.events().is_empty());
+
+            let frame = Frame::new(256, 64, [0; 256 * 64 / 2]);
+            block_on(display.flush_frame(&frame)).unwrap();
+        }
+
+        #[cfg(feature = "frame")]
+        #[test]
+        fn frames_taller_than_the_display_ram_are_rejected() {
+            let recorder = Recorder::new();
+            let mut display = recorder.display(Config::default());
+            block_on(display.init_default(&mut recorder.delay())).unwrap();
+            block_on(display.scroll_to(100)).unwrap();
+            recorder.clear();
+
+            let frame = Frame::new(4, 129, [0; 2 * 129]);
+            let result = block_on(display.flush_frame(&frame));
+            assert!(matches!(result, Err(Error::CommandError(CommandError::OutOfRange))));
+            assert!(recorder.events().is_empty());
+        }
+
+        #[test]
+        fn scroll_by_wraps_around_the_display_ram() {
+            let recorder = Recorder::new();
+            let mut display = recorder.display(Config::default());
+            block_on(display.scroll_to(100)).unwrap();
+            block_on(display.scroll_by(i16::MAX)).unwrap();
+            assert_eq!(display.scroll_position(), ((100 + i16::MAX as i32) % 128) as u8);
+            block_on(display.scroll_by(i16::MIN)).unwrap();
+            assert_eq!(display.scroll_position(), ((100 + i16::MAX as i32 + i16::MIN as i32) % 128) as u8);
+            block_on(display.scroll_by(-4)).unwrap();
+            assert_eq!(display.scroll_position(), 95);
+        }
+
+        #[test]
+        fn write_rows_rejects_widths_the_display_ram_cannot_hold() {
+            let recorder = Recorder::new();
+            let mut display = recorder.display(Config::default());
+            for width in [0, 6, 484] {
+                let result = block_on(display.write_rows(0, width, &[0; 484]));
+                assert!(matches!(result, Err(Error::CommandError(CommandError::OutOfRange))));
+            }
+            assert!(recorder.events().is_empty());
+            block_on(display.write_rows(0, 480, &[0; 240])).unwrap();
+        }
+
+        #[test]
+        fn empty_splash_is_rejected_before_anything_is_sent() {
+            let recorder = Recorder::new();
+            let mut display = recorder.display(Config::default());
+            let result = block_on(display.init_with_splash(&mut recorder.delay(), &[]));
+            assert!(matches!(result, Err(Error::CommandError(CommandError::OutOfRange))));
+            assert!(recorder.events().is_empty());
+        }
+
+        #[test]
+        fn splash_is_written_before_the_panel_is_turned_on() {
+            let recorder = Recorder::new();
+            let mut display = recorder.display(Config::default());
+            block_on(display.init_with_splash(&mut recorder.delay(), &[0x12; 4 * 64 / 2])).unwrap();
+            let transfers = recorder.transfers();
+            let splash = transfers.iter().position(|transfer| *transfer == Transfer::Data(vec![0x12; 128]));
+            assert!(splash.is_some());
+            assert!(transfers[..splash.unwrap()].contains(&Transfer::Command(Command::SetColumnAddress(59, 59))));
+            assert_eq!(transfers.last(), Some(&Transfer::Command(Command::SetSleepMode(false))));
+        }
+    }
+}
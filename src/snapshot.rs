@@ -0,0 +1,581 @@
+//! Golden image snapshot testing for frames.
+//!
+//! This module can save a `Frame` to, and load one from, 8-bit greyscale PGM and PNG images, and
+//! compare two frames pixel by pixel. `assert_snapshot` combines these to compare a rendered frame
+//! against a golden image on disk, so that a change to the rendered output fails a test. Golden
+//! images are created and updated by running the tests with `SSD1322_UPDATE_SNAPSHOTS` set.
+//!
+//! Grey levels 0-15 are stored as the 8-bit values 0, 17, 34, ..., 255. When loading an image, each
+//! value is rounded to the nearest grey level.
+//!
+//! > **This module is only available when the `snapshot` feature is enabled.**
+//!
+//! # Example
+//!
+//! ```no_run
+//! use embedded_graphics_core::pixelcolor::{Gray4, GrayColor};
+//! use ssd1322_rs::snapshot::assert_snapshot;
+//! use ssd1322_rs::{calculate_buffer_size, Frame};
+//!
+//! let mut frame: Frame<{ calculate_buffer_size(256, 64) }> = Frame::default();
+//! frame.set_pixel(10, 10, Gray4::WHITE);
+//! assert_snapshot(&frame, "tests/snapshots/home_screen.png");
+//! ```
+
+use crate::Frame;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+use std::{format, vec};
+
+/// Environment variable which, when set, makes `assert_snapshot` overwrite golden images with the
+/// frame being checked instead of comparing against them.
+pub const UPDATE_ENV_VAR: &str = "SSD1322_UPDATE_SNAPSHOTS";
+
+/// Errors that can occur when saving or loading snapshot images.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Reading or writing the image failed.
+    Io(io::Error),
+    /// The image is not a valid PGM image.
+    BadPgm,
+    /// The PNG image could not be encoded or decoded.
+    Png(&'static str),
+    /// The dimensions of the image do not fit the frame buffer being loaded.
+    SizeMismatch {
+        /// The width of the image in pixels.
+        width: u32,
+        /// The height of the image in pixels.
+        height: u32,
+    },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(err) => write!(f, "I/O error: {}", err),
+            SnapshotError::BadPgm => write!(f, "invalid PGM image"),
+            SnapshotError::Png(msg) => write!(f, "PNG error: {}", msg),
+            SnapshotError::SizeMismatch { width, height } => {
+                write!(f, "a {}x{} image does not fit the frame buffer", width, height)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<io::Error> for SnapshotError {
+    fn from(err: io::Error) -> Self {
+        SnapshotError::Io(err)
+    }
+}
+
+impl From<png::EncodingError> for SnapshotError {
+    fn from(err: png::EncodingError) -> Self {
+        match err {
+            png::EncodingError::IoError(err) => SnapshotError::Io(err),
+            _ => SnapshotError::Png("failed to encode image"),
+        }
+    }
+}
+
+impl From<png::DecodingError> for SnapshotError {
+    fn from(err: png::DecodingError) -> Self {
+        match err {
+            png::DecodingError::IoError(err) => SnapshotError::Io(err),
+            _ => SnapshotError::Png("failed to decode image"),
+        }
+    }
+}
+
+/// Converts a 4-bit grey level to an 8-bit grey value.
+fn to_gray8(level: u8) -> u8 {
+    level * 17
+}
+
+/// Converts an 8-bit grey value to the nearest 4-bit grey level.
+fn to_level(value: u8) -> u8 {
+    ((value as u16 + 8) / 17) as u8
+}
+
+/// Writes 8-bit greyscale pixels as a binary PGM image.
+fn write_pgm_pixels<W: Write>(
+    mut writer: W,
+    width: u16,
+    height: u16,
+    pixels: &[u8],
+) -> Result<(), SnapshotError> {
+    write!(writer, "P5\n{} {}\n255\n", width, height)?;
+    writer.write_all(pixels)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes 8-bit greyscale pixels as a PNG image.
+fn write_png_pixels<W: Write>(
+    writer: W,
+    width: u16,
+    height: u16,
+    pixels: &[u8],
+) -> Result<(), SnapshotError> {
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Reads the next whitespace separated token of a PGM header, skipping comments.
+fn pgm_token<R: BufRead>(reader: &mut R) -> Result<u32, SnapshotError> {
+    let mut token = Vec::new();
+    loop {
+        let mut byte = [0u8];
+        if reader.read(&mut byte)? == 0 {
+            break;
+        }
+        match byte[0] {
+            b'#' if token.is_empty() => {
+                reader.read_until(b'\n', &mut Vec::new())?;
+            }
+            b if b.is_ascii_whitespace() => {
+                if !token.is_empty() {
+                    break;
+                }
+            }
+            b => token.push(b),
+        }
+    }
+    std::str::from_utf8(&token)
+        .ok()
+        .and_then(|token| token.parse().ok())
+        .ok_or(SnapshotError::BadPgm)
+}
+
+impl<const N: usize> Frame<N> {
+    /// Returns the pixels of the frame as 8-bit grey values, row by row.
+    fn gray8_pixels(&self) -> Vec<u8> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| to_gray8(self.level(x, y).unwrap_or(0)))
+            .collect()
+    }
+
+    /// Creates a frame from 8-bit grey values, row by row.
+    fn from_gray8_pixels(width: u32, height: u32, pixels: &[u8]) -> Result<Self, SnapshotError> {
+        if width > u16::MAX as u32 || width as usize * height as usize != N * 2 {
+            return Err(SnapshotError::SizeMismatch { width, height });
+        }
+        let mut frame = Frame::new(width as u16, height as u16, [0; N]);
+        for (i, value) in pixels.iter().enumerate() {
+            let (x, y) = (i as u32 % width, i as u32 / width);
+            frame.set_level(x as u16, y as u16, to_level(*value));
+        }
+        Ok(frame)
+    }
+
+    /// Writes the frame as a binary, 8-bit greyscale PGM image.
+    pub fn write_pgm<W: Write>(&self, writer: W) -> Result<(), SnapshotError> {
+        write_pgm_pixels(writer, self.width, self.height, &self.gray8_pixels())
+    }
+
+    /// Reads a frame from a binary PGM image. The image dimensions must fit the frame buffer
+    /// exactly.
+    pub fn read_pgm<R: Read>(reader: R) -> Result<Self, SnapshotError> {
+        let mut reader = BufReader::new(reader);
+        let mut magic = [0u8; 2];
+        reader.read_exact(&mut magic)?;
+        if &magic != b"P5" {
+            return Err(SnapshotError::BadPgm);
+        }
+        let width = pgm_token(&mut reader)?;
+        let height = pgm_token(&mut reader)?;
+        let max = pgm_token(&mut reader)?;
+        if max == 0 || max > 255 {
+            return Err(SnapshotError::BadPgm);
+        }
+        if width as usize * height as usize != N * 2 {
+            return Err(SnapshotError::SizeMismatch { width, height });
+        }
+        let mut pixels = vec![0u8; N * 2];
+        reader.read_exact(&mut pixels)?;
+        for value in pixels.iter_mut() {
+            *value = ((*value as u32 * 255 + max / 2) / max) as u8;
+        }
+        Self::from_gray8_pixels(width, height, &pixels)
+    }
+
+    /// Writes the frame as an 8-bit greyscale PNG image.
+    pub fn write_png<W: Write>(&self, writer: W) -> Result<(), SnapshotError> {
+        write_png_pixels(writer, self.width, self.height, &self.gray8_pixels())
+    }
+
+    /// Reads a frame from a greyscale PNG image. The image dimensions must fit the frame buffer
+    /// exactly.
+    pub fn read_png<R: Read>(reader: R) -> Result<Self, SnapshotError> {
+        let mut decoder = png::Decoder::new(reader);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0u8; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf)?;
+        let samples = match info.color_type {
+            png::ColorType::Grayscale => 1,
+            png::ColorType::GrayscaleAlpha => 2,
+            _ => return Err(SnapshotError::Png("image is not greyscale")),
+        };
+        let pixels: Vec<u8> = buf[..info.buffer_size()]
+            .chunks(info.line_size)
+            .flat_map(|line| line.iter().step_by(samples).take(info.width as usize))
+            .copied()
+            .collect();
+        Self::from_gray8_pixels(info.width, info.height, &pixels)
+    }
+
+    /// Saves the frame to `path`, as a PNG image if the extension is `png` and as a PGM image
+    /// otherwise.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        let path = path.as_ref();
+        let writer = BufWriter::new(File::create(path)?);
+        if is_png(path) {
+            self.write_png(writer)
+        } else {
+            self.write_pgm(writer)
+        }
+    }
+
+    /// Loads a frame from `path`, as a PNG image if the extension is `png` and as a PGM image
+    /// otherwise.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SnapshotError> {
+        let path = path.as_ref();
+        let reader = BufReader::new(File::open(path)?);
+        if is_png(path) {
+            Self::read_png(reader)
+        } else {
+            Self::read_pgm(reader)
+        }
+    }
+}
+
+fn is_png(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+}
+
+/// A single pixel which differs between two frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelDifference {
+    /// The x-coordinate of the pixel.
+    pub x: u16,
+    /// The y-coordinate of the pixel.
+    pub y: u16,
+    /// The grey level of the pixel in the expected frame.
+    pub expected: u8,
+    /// The grey level of the pixel in the actual frame.
+    pub actual: u8,
+}
+
+/// The result of comparing two frames with `compare`.
+#[derive(Clone, Debug)]
+pub struct Comparison {
+    /// The width of the compared area in pixels.
+    pub width: u16,
+    /// The height of the compared area in pixels.
+    pub height: u16,
+    /// Whether the two frames have the same dimensions.
+    pub same_size: bool,
+    /// Every pixel which differs between the two frames, row by row. Pixels outside of one of
+    /// the frames are compared as grey level 0.
+    pub differences: Vec<PixelDifference>,
+    /// The grey levels of the actual frame, row by row.
+    actual: Vec<u8>,
+}
+
+impl Comparison {
+    /// Whether the two frames are identical.
+    pub fn is_match(&self) -> bool {
+        self.same_size && self.differences.is_empty()
+    }
+
+    /// Builds the diff image as 8-bit grey values: pixels which match are drawn at a quarter of
+    /// their brightness, and pixels which differ are drawn at full brightness.
+    fn diff_pixels(&self) -> Vec<u8> {
+        let mut pixels: Vec<u8> = self.actual.iter().map(|level| to_gray8(*level) / 4).collect();
+        for difference in &self.differences {
+            pixels[difference.y as usize * self.width as usize + difference.x as usize] = 255;
+        }
+        pixels
+    }
+
+    /// Writes a PGM diff image, in which differing pixels are bright and matching pixels are
+    /// dimmed.
+    pub fn write_diff_pgm<W: Write>(&self, writer: W) -> Result<(), SnapshotError> {
+        write_pgm_pixels(writer, self.width, self.height, &self.diff_pixels())
+    }
+
+    /// Writes a PNG diff image, in which differing pixels are bright and matching pixels are
+    /// dimmed.
+    pub fn write_diff_png<W: Write>(&self, writer: W) -> Result<(), SnapshotError> {
+        write_png_pixels(writer, self.width, self.height, &self.diff_pixels())
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.same_size {
+            writeln!(f, "frames differ in size")?;
+        }
+        write!(f, "{} pixel(s) differ", self.differences.len())?;
+        for difference in self.differences.iter().take(10) {
+            write!(
+                f,
+                "\n  ({}, {}): expected {}, found {}",
+                difference.x, difference.y, difference.expected, difference.actual
+            )?;
+        }
+        if self.differences.len() > 10 {
+            write!(f, "\n  ...")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two frames pixel by pixel.
+pub fn compare<const N: usize, const M: usize>(
+    expected: &Frame<N>,
+    actual: &Frame<M>,
+) -> Comparison {
+    let width = expected.width.max(actual.width);
+    let height = expected.height.max(actual.height);
+    let mut differences = Vec::new();
+    let mut actual_levels = Vec::with_capacity(width as usize * height as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let e = expected.level(x, y).unwrap_or(0);
+            let a = actual.level(x, y).unwrap_or(0);
+            actual_levels.push(a);
+            if e != a {
+                differences.push(PixelDifference { x, y, expected: e, actual: a });
+            }
+        }
+    }
+    Comparison {
+        width,
+        height,
+        same_size: expected.width == actual.width && expected.height == actual.height,
+        differences,
+        actual: actual_levels,
+    }
+}
+
+/// Asserts that `frame` matches the golden image at `path`.
+///
+/// If the `SSD1322_UPDATE_SNAPSHOTS` environment variable is set, the frame is saved as the new
+/// golden image instead. Otherwise the frame is compared with the golden image and, if they
+/// differ, the frame and a diff image are saved next to the golden image (with `.actual` and
+/// `.diff` inserted before the extension) before panicking.
+///
+/// # Panics
+///
+/// If the golden image does not exist and `SSD1322_UPDATE_SNAPSHOTS` is not set, if the frame does
+/// not match the golden image, or if the images cannot be read or written.
+#[track_caller]
+pub fn assert_snapshot<const N: usize, P: AsRef<Path>>(frame: &Frame<N>, path: P) {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("failed to create snapshot directory");
+        }
+        frame.save(path).expect("failed to save snapshot");
+        return;
+    }
+    if !path.exists() {
+        panic!(
+            "snapshot {} does not exist; run with {} set to create it",
+            path.display(),
+            UPDATE_ENV_VAR
+        );
+    }
+
+    let expected = Frame::<N>::load(path)
+        .unwrap_or_else(|err| panic!("failed to load snapshot {}: {}", path.display(), err));
+    let comparison = compare(&expected, frame);
+    if comparison.is_match() {
+        return;
+    }
+
+    let actual_path = sibling_path(path, "actual");
+    let diff_path = sibling_path(path, "diff");
+    frame.save(&actual_path).expect("failed to save actual frame");
+    let writer = BufWriter::new(File::create(&diff_path).expect("failed to create diff image"));
+    if is_png(&diff_path) {
+        comparison.write_diff_png(writer)
+    } else {
+        comparison.write_diff_pgm(writer)
+    }
+    .expect("failed to save diff image");
+
+    panic!(
+        "frame does not match snapshot {}: {}\nactual: {}\ndiff: {}",
+        path.display(),
+        comparison,
+        actual_path.display(),
+        diff_path.display()
+    );
+}
+
+/// Inserts `tag` before the extension of `path`, e.g. `screen.png` becomes `screen.diff.png`.
+fn sibling_path(path: &Path, tag: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(ext) => path.with_file_name(format!("{}.{}.{}", stem, tag, ext.to_string_lossy())),
+        None => path.with_file_name(format!("{}.{}", stem, tag)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::{String, ToString};
+
+    #[test]
+    fn missing_golden_image_fails() {
+        if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+            return;
+        }
+        let path = std::env::temp_dir().join("ssd1322_rs_missing_snapshot.pgm");
+        let _ = std::fs::remove_file(&path);
+        let frame = Frame::new(4, 1, [0u8; 2]);
+
+        let panic = std::panic::catch_unwind(|| assert_snapshot(&frame, &path)).unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains("ssd1322_rs_missing_snapshot.pgm"));
+        assert!(message.contains(UPDATE_ENV_VAR));
+        assert!(!path.exists());
+    }
+
+    // A frame with every grey level, 8x2 pixels
+    fn ramp() -> Frame<8> {
+        let mut frame = Frame::new(8, 2, [0u8; 8]);
+        for level in 0..16u8 {
+            frame.set_level(level as u16 % 8, level as u16 / 8, level);
+        }
+        frame
+    }
+
+    // A path in the temporary directory, removing any file left there by an earlier run
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("ssd1322_rs_{}_{}", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn pgm_round_trips() {
+        let mut bytes = Vec::new();
+        ramp().write_pgm(&mut bytes).unwrap();
+        assert!(bytes.starts_with(b"P5\n8 2\n255\n"));
+        assert_eq!(bytes[11..], [0, 17, 34, 51, 68, 85, 102, 119, 136, 153, 170, 187, 204, 221, 238, 255]);
+        let frame = Frame::<8>::read_pgm(&bytes[..]).unwrap();
+        assert!(compare(&ramp(), &frame).is_match());
+    }
+
+    #[test]
+    fn pgm_header_comments_are_skipped_and_maxval_is_scaled() {
+        let mut bytes = b"P5\n# created by hand\n4 # width\n1\n15\n".to_vec();
+        bytes.extend_from_slice(&[0, 5, 10, 15]);
+        let frame = Frame::<2>::read_pgm(&bytes[..]).unwrap();
+        let levels: Vec<_> = (0..4).map(|x| frame.level(x, 0).unwrap()).collect();
+        assert_eq!(levels, [0, 5, 10, 15]);
+    }
+
+    #[test]
+    fn pgm_with_bad_header_or_size_is_rejected() {
+        assert!(matches!(Frame::<2>::read_pgm(&b"P2\n4 1\n255\n"[..]), Err(SnapshotError::BadPgm)));
+        assert!(matches!(Frame::<2>::read_pgm(&b"P5\n4 1\n0\n"[..]), Err(SnapshotError::BadPgm)));
+        assert!(matches!(
+            Frame::<2>::read_pgm(&b"P5\n8 1\n255\n"[..]),
+            Err(SnapshotError::SizeMismatch { width: 8, height: 1 })
+        ));
+    }
+
+    #[test]
+    fn png_round_trips() {
+        let mut bytes = Vec::new();
+        ramp().write_png(&mut bytes).unwrap();
+        let frame = Frame::<8>::read_png(&bytes[..]).unwrap();
+        assert!(compare(&ramp(), &frame).is_match());
+        assert!(matches!(Frame::<4>::read_png(&bytes[..]), Err(SnapshotError::SizeMismatch { .. })));
+    }
+
+    #[test]
+    fn save_and_load_pick_the_format_from_the_extension() {
+        for name in ["ramp.pgm", "ramp.PNG"] {
+            let path = temp_path(name);
+            ramp().save(&path).unwrap();
+            let png = std::fs::read(&path).unwrap().starts_with(b"\x89PNG");
+            assert_eq!(png, name.ends_with("PNG"));
+            assert!(compare(&ramp(), &Frame::<8>::load(&path).unwrap()).is_match());
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn compare_lists_differing_pixels() {
+        let mut frame = ramp();
+        frame.set_level(1, 0, 9);
+        frame.set_level(7, 1, 0);
+        let comparison = compare(&ramp(), &frame);
+        assert!(comparison.same_size);
+        assert!(!comparison.is_match());
+        assert_eq!(
+            comparison.differences,
+            [
+                PixelDifference { x: 1, y: 0, expected: 1, actual: 9 },
+                PixelDifference { x: 7, y: 1, expected: 15, actual: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn compare_reports_a_size_mismatch() {
+        let comparison = compare(&ramp(), &Frame::new(4, 1, [0u8; 2]));
+        assert!(!comparison.same_size);
+        assert!(!comparison.is_match());
+        assert_eq!((comparison.width, comparison.height), (8, 2));
+        // Every lit pixel of the larger frame differs from the missing pixels of the smaller one
+        assert_eq!(comparison.differences.len(), 15);
+        assert!(comparison.to_string().starts_with("frames differ in size\n15 pixel(s) differ"));
+    }
+
+    #[test]
+    fn mismatch_writes_actual_and_diff_images() {
+        if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+            return;
+        }
+        let path = temp_path("mismatch.pgm");
+        let actual_path = sibling_path(&path, "actual");
+        let diff_path = sibling_path(&path, "diff");
+        ramp().save(&path).unwrap();
+        let mut frame = ramp();
+        frame.set_level(0, 0, 15);
+
+        let panic = std::panic::catch_unwind(|| assert_snapshot(&frame, &path)).unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains("1 pixel(s) differ"));
+        assert!(message.contains(&*actual_path.to_string_lossy()));
+
+        assert!(compare(&frame, &Frame::<8>::load(&actual_path).unwrap()).is_match());
+        // The differing pixel is at full brightness, the rest at a quarter of theirs
+        let diff = std::fs::read(&diff_path).unwrap();
+        assert_eq!(diff[11..14], [255, 17 / 4, 34 / 4]);
+
+        assert_snapshot(&ramp(), &path);
+        for path in [path, actual_path, diff_path] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,466 @@
+//! Host-side mock interfaces that record the traffic sent by the driver.
+//!
+//! The mocks in this module implement `SpiDevice`, `OutputPin` and `DelayNs`, and record every byte
+//! written (together with the state of the data/command pin), every pin change and every delay
+//! requested. The recorded traffic can then be inspected as raw events, or decoded back into the
+//! commands and data payloads that the driver sent.
+//!
+//! > **This module is only available when the `testing` feature is enabled.**
+//!
+//! # Example
+//!
+//! ```
+//! use ssd1322_rs::instruction::Command;
+//! use ssd1322_rs::testing::{block_on, Recorder};
+//! use ssd1322_rs::Config;
+//!
+//! let recorder = Recorder::new();
+//! let mut display = recorder.display(Config::default());
+//! block_on(display.init_default(&mut recorder.delay())).unwrap();
+//!
+//! assert!(recorder.commands().contains(&Command::SetContrastCurrent(0x3C)));
+//! ```
+
+use crate::instruction::{Command, CommandError};
+use crate::{Config, SSD1322};
+use core::cell::RefCell;
+use core::convert::Infallible;
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+use embedded_hal::digital::{ErrorType as PinErrorType, OutputPin};
+use embedded_hal::spi::{ErrorType as SpiErrorType, Operation};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::SpiDevice;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::Wake;
+use std::vec::Vec;
+
+/// Identifies which of the driver's pins a mock pin stands in for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PinId {
+    /// The data/command pin.
+    DataCommand,
+    /// The reset pin.
+    Reset,
+    /// The power on pin.
+    Power,
+}
+
+/// A single event recorded by the mocks, in the order it happened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Bytes written over SPI. `dc` is the state of the data/command pin at the time of the
+    /// write: `false` for command bytes and `true` for data bytes.
+    Write {
+        /// State of the data/command pin during the write.
+        dc: bool,
+        /// The bytes written.
+        bytes: Vec<u8>,
+    },
+    /// A pin was set high (`true`) or low (`false`).
+    Pin {
+        /// The pin that was set.
+        pin: PinId,
+        /// The new state of the pin.
+        high: bool,
+    },
+    /// A delay was requested, in nanoseconds.
+    Delay(u64),
+}
+
+/// A decoded unit of traffic sent to the display.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Transfer {
+    /// A command that decoded successfully.
+    Command(Command),
+    /// A command byte and arguments that could not be decoded into a `Command`, along with the
+    /// reason decoding failed.
+    Raw {
+        /// The command byte.
+        cmd: u8,
+        /// The argument bytes sent after the command byte.
+        args: Vec<u8>,
+        /// Why the command could not be decoded.
+        error: CommandError,
+    },
+    /// Data bytes sent to the display RAM after a `WriteRam` command. Consecutive writes are
+    /// merged into a single payload.
+    Data(Vec<u8>),
+}
+
+#[derive(Default)]
+struct State {
+    events: Vec<Event>,
+    dc: bool,
+}
+
+/// Records the traffic of a set of mock interfaces.
+///
+/// A `Recorder` hands out mock SPI, pin and delay implementations that all share it, so that a
+/// single ordered log of the traffic is kept. Cloning a `Recorder` yields another handle onto the
+/// same log.
+#[derive(Clone, Default)]
+pub struct Recorder {
+    state: Rc<RefCell<State>>,
+}
+
+impl Recorder {
+    /// Creates a new, empty recorder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a mock SPI device recording into this recorder.
+    pub fn spi(&self) -> MockSpi {
+        MockSpi {
+            recorder: self.clone(),
+        }
+    }
+
+    /// Creates a mock data/command pin recording into this recorder.
+    pub fn dc(&self) -> MockPin {
+        self.pin(PinId::DataCommand)
+    }
+
+    /// Creates a mock reset pin recording into this recorder.
+    pub fn rst(&self) -> MockPin {
+        self.pin(PinId::Reset)
+    }
+
+    /// Creates a mock power on pin recording into this recorder.
+    pub fn power(&self) -> MockPin {
+        self.pin(PinId::Power)
+    }
+
+    fn pin(&self, id: PinId) -> MockPin {
+        MockPin {
+            id,
+            recorder: self.clone(),
+        }
+    }
+
+    /// Creates a mock delay recording into this recorder. Delays complete immediately.
+    pub fn delay(&self) -> MockDelay {
+        MockDelay {
+            recorder: self.clone(),
+        }
+    }
+
+    /// Creates a display driver wired up to mocks recording into this recorder.
+    pub fn display(&self, config: Config) -> SSD1322<MockSpi, MockPin, MockPin, MockPin> {
+        SSD1322::new(self.spi(), self.dc(), self.rst(), self.power(), config)
+    }
+
+    fn push(&self, event: Event) {
+        self.state.borrow_mut().events.push(event);
+    }
+
+    /// Discards all of the traffic recorded so far.
+    pub fn clear(&self) {
+        self.state.borrow_mut().events.clear();
+    }
+
+    /// Returns every event recorded so far.
+    pub fn events(&self) -> Vec<Event> {
+        self.state.borrow().events.clone()
+    }
+
+    /// Returns every byte written so far, together with the state of the data/command pin when it
+    /// was written.
+    pub fn bytes(&self) -> Vec<(bool, u8)> {
+        self.state
+            .borrow()
+            .events
+            .iter()
+            .flat_map(|event| match event {
+                Event::Write { dc, bytes } => bytes.iter().map(|b| (*dc, *b)).collect(),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
+    /// Returns every delay requested so far, in nanoseconds.
+    pub fn delays(&self) -> Vec<u64> {
+        self.state
+            .borrow()
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Delay(ns) => Some(*ns),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Decodes the bytes written so far into commands and data payloads.
+    pub fn transfers(&self) -> Vec<Transfer> {
+        decode_transfers(&self.state.borrow().events)
+    }
+
+    /// Returns every command written so far that decoded successfully.
+    pub fn commands(&self) -> Vec<Command> {
+        self.transfers()
+            .into_iter()
+            .filter_map(|transfer| match transfer {
+                Transfer::Command(command) => Some(command),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Decodes a sequence of events into commands and data payloads.
+///
+/// Each byte written with the data/command pin low starts a new command, and the data bytes that
+/// follow it are its arguments. Data bytes following a `WriteRam` command (or not preceded by any
+/// command) are collected into `Transfer::Data` payloads instead.
+pub fn decode_transfers(events: &[Event]) -> Vec<Transfer> {
+    let mut transfers = Vec::new();
+    let mut pending: Option<(u8, Vec<u8>)> = None;
+    let mut payload: Option<Vec<u8>> = None;
+
+    fn finish(
+        transfers: &mut Vec<Transfer>,
+        pending: &mut Option<(u8, Vec<u8>)>,
+        payload: &mut Option<Vec<u8>>,
+    ) {
+        if let Some((cmd, args)) = pending.take() {
+            transfers.push(match Command::decode(cmd, &args) {
+                Ok(command) => Transfer::Command(command),
+                Err(error) => Transfer::Raw { cmd, args, error },
+            });
+        }
+        if let Some(data) = payload.take() {
+            transfers.push(Transfer::Data(data));
+        }
+    }
+
+    for event in events {
+        let Event::Write { dc, bytes } = event else {
+            continue;
+        };
+        if *dc {
+            match &mut pending {
+                Some((_, args)) => args.extend_from_slice(bytes),
+                None => payload.get_or_insert_with(Vec::new).extend_from_slice(bytes),
+            }
+        } else {
+            for &cmd in bytes {
+                finish(&mut transfers, &mut pending, &mut payload);
+                if cmd == 0x5C {
+                    transfers.push(Transfer::Command(Command::WriteRam));
+                } else {
+                    pending = Some((cmd, Vec::new()));
+                }
+            }
+        }
+    }
+    finish(&mut transfers, &mut pending, &mut payload);
+    transfers
+}
+
+/// A mock SPI device. Reads return zeros.
+pub struct MockSpi {
+    recorder: Recorder,
+}
+
+impl SpiErrorType for MockSpi {
+    type Error = Infallible;
+}
+
+impl SpiDevice for MockSpi {
+    async fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+        for operation in operations {
+            let write = match operation {
+                Operation::Read(buf) => {
+                    buf.fill(0);
+                    None
+                }
+                Operation::Write(buf) => Some(buf.to_vec()),
+                Operation::Transfer(read, write) => {
+                    read.fill(0);
+                    Some(write.to_vec())
+                }
+                Operation::TransferInPlace(buf) => {
+                    let written = buf.to_vec();
+                    buf.fill(0);
+                    Some(written)
+                }
+                Operation::DelayNs(ns) => {
+                    self.recorder.push(Event::Delay(*ns as u64));
+                    None
+                }
+            };
+            if let Some(bytes) = write {
+                let dc = self.recorder.state.borrow().dc;
+                self.recorder.push(Event::Write { dc, bytes });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A mock output pin.
+pub struct MockPin {
+    id: PinId,
+    recorder: Recorder,
+}
+
+impl MockPin {
+    fn set(&mut self, high: bool) {
+        if self.id == PinId::DataCommand {
+            self.recorder.state.borrow_mut().dc = high;
+        }
+        self.recorder.push(Event::Pin { pin: self.id, high });
+    }
+}
+
+impl PinErrorType for MockPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for MockPin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.set(true);
+        Ok(())
+    }
+}
+
+/// A mock delay, which records the requested delay and completes immediately.
+pub struct MockDelay {
+    recorder: Recorder,
+}
+
+impl DelayNs for MockDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.recorder.push(Event::Delay(ns as u64));
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.recorder.push(Event::Delay(us as u64 * 1_000));
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.recorder.push(Event::Delay(ms as u64 * 1_000_000));
+    }
+}
+
+// A waker for `block_on`, which polls continuously and so never needs waking
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Runs a future to completion on the current thread.
+///
+/// This is a minimal executor for driving the display with the mocks in this module, none of
+/// which ever wait. It busy-polls, so it is not suitable for futures that wait on other events.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::DisplayMode;
+    use std::vec;
+
+    fn write(dc: bool, bytes: &[u8]) -> Event {
+        Event::Write { dc, bytes: bytes.to_vec() }
+    }
+
+    #[test]
+    fn arguments_split_across_writes_are_joined() {
+        let events = [write(false, &[0x15]), write(true, &[28]), write(true, &[91])];
+        assert_eq!(decode_transfers(&events), [Transfer::Command(Command::SetColumnAddress(28, 91))]);
+    }
+
+    #[test]
+    fn command_bytes_merged_into_one_write_are_separated() {
+        let events = [write(false, &[0xA6, 0xA9, 0xAF])];
+        assert_eq!(
+            decode_transfers(&events),
+            [
+                Transfer::Command(Command::SetDisplayMode(DisplayMode::Normal)),
+                Transfer::Command(Command::DisablePartialDisplay),
+                Transfer::Command(Command::SetSleepMode(false)),
+            ]
+        );
+    }
+
+    #[test]
+    fn data_after_write_ram_is_merged_until_the_next_command() {
+        let events = [
+            write(false, &[0x5C]),
+            write(true, &[1, 2]),
+            Event::Delay(1_000),
+            write(true, &[3]),
+            write(false, &[0xA1]),
+            write(true, &[4]),
+        ];
+        assert_eq!(
+            decode_transfers(&events),
+            [
+                Transfer::Command(Command::WriteRam),
+                Transfer::Data(vec![1, 2, 3]),
+                Transfer::Command(Command::SetStartLine(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn data_without_a_command_is_a_payload() {
+        let events = [write(true, &[0xAB, 0xCD])];
+        assert_eq!(decode_transfers(&events), [Transfer::Data(vec![0xAB, 0xCD])]);
+    }
+
+    #[test]
+    fn undecodable_commands_are_raw() {
+        // Arguments sent with the data/command pin low are command bytes of their own
+        let events = [write(false, &[0xC1, 0x80]), write(false, &[0xC7]), write(true, &[0x10])];
+        assert_eq!(
+            decode_transfers(&events),
+            [
+                Transfer::Raw { cmd: 0xC1, args: vec![], error: CommandError::BadArgumentLength },
+                Transfer::Raw { cmd: 0x80, args: vec![], error: CommandError::UnknownCommand },
+                Transfer::Raw { cmd: 0xC7, args: vec![0x10], error: CommandError::OutOfRange },
+            ]
+        );
+    }
+
+    #[test]
+    fn recorded_init_decodes_without_raw_commands() {
+        let recorder = Recorder::new();
+        let mut display = recorder.display(Config { clear_on_init: true, ..Config::default() });
+        block_on(display.init_default(&mut recorder.delay())).unwrap();
+
+        let transfers = recorder.transfers();
+        assert!(!transfers.iter().any(|transfer| matches!(transfer, Transfer::Raw { .. })));
+        assert_eq!(transfers.first(), Some(&Transfer::Command(Command::SetCommandLock(false))));
+        assert_eq!(transfers.last(), Some(&Transfer::Command(Command::SetSleepMode(false))));
+        // The display RAM is cleared one row at a time
+        let cleared: usize = transfers
+            .iter()
+            .map(|transfer| match transfer {
+                Transfer::Data(data) => data.len(),
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(cleared, 240 * 128);
+    }
+}
@@ -0,0 +1,578 @@
+//! A typestate wrapper around the driver which tracks the display's lifecycle at compile time.
+//!
+//! `SSD1322` itself allows any method to be called at any time, so nothing stops a frame being
+//! flushed before the display is initialised, or drawing to a panel that has been put to sleep.
+//! `Display` wraps the driver and encodes its state in a type parameter instead:
+//!
+//! - `Uninitialised`: freshly created. The only thing to do is initialise it.
+//! - `Active`: initialised and displaying. The methods which draw, flush and adjust the image are
+//!   available; the ones which change the power state are replaced by transitions.
+//! - `Sleeping`: the panel is off (sleep mode on). It can only be woken.
+//! - `Locked`: the command lock is set, so the controller ignores every command except unlock. It
+//!   can only be unlocked.
+//! - `PoweredOff`: the panel is off and VCC has been switched off with `SSD1322::shutdown`. It can
+//!   only be powered back on.
+//!
+//! Each transition consumes the display and returns it in its new state. If a transition fails,
+//! the display is returned alongside the error in a `TransitionError`, still in its old state.
+//!
+//! Since the wrapper must see every change of state, an `IdlePolicy` which puts the panel to sleep
+//! can't be used with it (dimming is fine), and `into_inner` gives up the state tracking to reach
+//! the rest of the driver, such as `write_raw_command`.
+//!
+//! # Example
+//!
+//! ```
+//! # use ssd1322_rs::{Config, Error};
+//! # use ssd1322_rs::typestate::{Display, TransitionError};
+//! # use embedded_hal_async::delay::DelayNs;
+//! # use core::convert::Infallible;
+//! # use embedded_hal::digital::OutputPin;
+//! # use embedded_hal_async::spi::SpiDevice;
+//! # async fn example_usage<SPI, DC, RST, PO, E, D>(spi: SPI, dc: DC, rst: RST, power: PO, delay: &mut D) -> Result<(), Error<E>>
+//! # where
+//! #     SPI: SpiDevice<Error = E>,
+//! #     DC: OutputPin<Error = Infallible>,
+//! #     RST: OutputPin<Error = Infallible>,
+//! #     PO: OutputPin<Error = Infallible>,
+//! #     D: DelayNs,
+//! # {
+//! let display = Display::new(spi, dc, rst, power, Config::default());
+//! let mut display = display.init_default(delay).await.map_err(TransitionError::into_error)?;
+//!
+//! display.flush_buffer(&[0xFF; 128 * 64]).await?;
+//!
+//! let display = display.sleep().await.map_err(TransitionError::into_error)?;
+//! // display.flush_buffer(..) does not compile here
+//! let display = display.wake().await.map_err(TransitionError::into_error)?;
+//! # let _ = display;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! An active display can't be put to sleep or powered off without a transition:
+//!
+//! ```compile_fail,E0507
+//! # use ssd1322_rs::Error;
+//! # use ssd1322_rs::typestate::{Active, Display, TransitionError};
+//! # use embedded_hal_async::delay::DelayNs;
+//! # use core::convert::Infallible;
+//! # use embedded_hal::digital::OutputPin;
+//! # use embedded_hal_async::spi::SpiDevice;
+//! # async fn example_usage<SPI, DC, RST, PO, E, D>(display: &mut Display<SPI, DC, RST, PO, Active>, delay: &mut D) -> Result<(), Error<E>>
+//! # where
+//! #     SPI: SpiDevice<Error = E>,
+//! #     DC: OutputPin<Error = Infallible>,
+//! #     RST: OutputPin<Error = Infallible>,
+//! #     PO: OutputPin<Error = Infallible>,
+//! #     D: DelayNs,
+//! # {
+//! display.shutdown(delay).await.map_err(TransitionError::into_error)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::instruction::{Command, CommandError, DisplayMode};
+#[cfg(feature = "frame")]
+use crate::Frame;
+use crate::{Config, Error, IdlePolicy, Orientation, SSD1322};
+use core::convert::Infallible;
+use core::marker::PhantomData;
+use core::ops::RangeInclusive;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::SpiDevice;
+
+/// State of a display which has not been initialised yet.
+pub struct Uninitialised;
+
+/// State of a display which is initialised and displaying.
+pub struct Active;
+
+/// State of a display whose panel is asleep.
+pub struct Sleeping;
+
+/// State of a display whose command lock is set.
+pub struct Locked;
+
+/// State of a display whose VCC has been switched off.
+pub struct PoweredOff;
+
+/// An `SSD1322` driver whose lifecycle state is tracked by the type parameter `S`.
+///
+/// See the [module documentation](self) for the available states.
+pub struct Display<SPI, DC, RST, PO, S>
+where
+    SPI: SpiDevice,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    PO: OutputPin<Error = Infallible>,
+{
+    driver: SSD1322<SPI, DC, RST, PO>,
+    _state: PhantomData<S>,
+}
+
+/// A failed state transition: the error, and the display in the state it was in beforehand.
+pub struct TransitionError<T, E> {
+    /// The display, still in its previous state.
+    pub display: T,
+    /// The error which caused the transition to fail.
+    pub error: Error<E>,
+}
+
+impl<T, E> TransitionError<T, E> {
+    /// Discards the display and returns the error.
+    pub fn into_error(self) -> Error<E> {
+        self.error
+    }
+}
+
+impl<T, E: core::fmt::Debug> core::fmt::Debug for TransitionError<T, E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TransitionError").field("error", &self.error).finish_non_exhaustive()
+    }
+}
+
+impl<SPI, DC, RST, PO, E, S> Display<SPI, DC, RST, PO, S>
+where
+    SPI: SpiDevice<Error = E>,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    PO: OutputPin<Error = Infallible>,
+{
+    fn into_state<T>(self) -> Display<SPI, DC, RST, PO, T> {
+        Display {
+            driver: self.driver,
+            _state: PhantomData,
+        }
+    }
+
+    // Sends a command, then moves to state `T` on success or stays in state `S` on failure
+    async fn transition<T>(
+        mut self,
+        command: Command,
+    ) -> Result<Display<SPI, DC, RST, PO, T>, TransitionError<Self, E>> {
+        let result = match command.prepare() {
+            Ok(command) => self.driver.write_command(&command).await,
+            Err(error) => Err(error.into()),
+        };
+        match result {
+            Ok(()) => Ok(self.into_state()),
+            Err(error) => Err(TransitionError {
+                display: self,
+                error,
+            }),
+        }
+    }
+
+    // Runs the power-on or power-off sequence, then moves to state `T` on success or stays in state
+    // `S` on failure
+    async fn power_transition<T, D>(
+        mut self,
+        delay: &mut D,
+        on: bool,
+    ) -> Result<Display<SPI, DC, RST, PO, T>, TransitionError<Self, E>>
+    where
+        D: DelayNs,
+    {
+        let result = if on {
+            self.driver.power_on(delay).await
+        } else {
+            self.driver.shutdown(delay).await
+        };
+        match result {
+            Ok(()) => Ok(self.into_state()),
+            Err(error) => Err(TransitionError {
+                display: self,
+                error,
+            }),
+        }
+    }
+
+    /// Returns the wrapped driver, giving up the state tracking.
+    pub fn into_inner(self) -> SSD1322<SPI, DC, RST, PO> {
+        self.driver
+    }
+}
+
+impl<SPI, DC, RST, PO, E> Display<SPI, DC, RST, PO, Uninitialised>
+where
+    SPI: SpiDevice<Error = E>,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    PO: OutputPin<Error = Infallible>,
+{
+    /// Creates a new, uninitialised display. See `SSD1322::new`.
+    pub fn new(spi: SPI, dc: DC, rst: RST, power: PO, config: Config) -> Self {
+        Self {
+            driver: SSD1322::new(spi, dc, rst, power, config),
+            _state: PhantomData,
+        }
+    }
+
+    // The wrapper can't follow an idle policy putting the panel to sleep behind its back
+    fn check_idle_policy(&self) -> Result<(), Error<E>> {
+        match self.driver.idle_policy {
+            Some(IdlePolicy { sleep_after_ms: Some(_), .. }) => {
+                Err(Error::CommandError(CommandError::OutOfRange))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Initialises the display with `SSD1322::init_default`, and returns it in the active state.
+    ///
+    /// # Parameters
+    ///
+    /// - `delay`: A mutable reference to an implementation of the `DelayNs` trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `SSD1322::init_default` and the display, still uninitialised, or
+    /// `Error::CommandError(CommandError::OutOfRange)` if `Config::idle_policy` puts the panel to
+    /// sleep.
+    pub async fn init_default<D>(
+        mut self,
+        delay: &mut D,
+    ) -> Result<Display<SPI, DC, RST, PO, Active>, TransitionError<Self, E>>
+    where
+        D: DelayNs,
+    {
+        if let Err(error) = self.check_idle_policy() {
+            return Err(TransitionError { display: self, error });
+        }
+        match self.driver.init_default(delay).await {
+            Ok(()) => Ok(self.into_state()),
+            Err(error) => Err(TransitionError {
+                display: self,
+                error,
+            }),
+        }
+    }
+
+    /// Initialises the display with `SSD1322::init_with_splash`, and returns it in the active
+    /// state.
+    ///
+    /// # Parameters
+    ///
+    /// - `delay`: A mutable reference to an implementation of the `DelayNs` trait.
+    /// - `splash`: The splash image to show. See `SSD1322::init_with_splash`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `SSD1322::init_with_splash` and the display, still uninitialised, or
+    /// `Error::CommandError(CommandError::OutOfRange)` if `Config::idle_policy` puts the panel to
+    /// sleep.
+    pub async fn init_with_splash<D>(
+        mut self,
+        delay: &mut D,
+        splash: &[u8],
+    ) -> Result<Display<SPI, DC, RST, PO, Active>, TransitionError<Self, E>>
+    where
+        D: DelayNs,
+    {
+        if let Err(error) = self.check_idle_policy() {
+            return Err(TransitionError { display: self, error });
+        }
+        match self.driver.init_with_splash(delay, splash).await {
+            Ok(()) => Ok(self.into_state()),
+            Err(error) => Err(TransitionError {
+                display: self,
+                error,
+            }),
+        }
+    }
+}
+
+impl<SPI, DC, RST, PO, E> Display<SPI, DC, RST, PO, Active>
+where
+    SPI: SpiDevice<Error = E>,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    PO: OutputPin<Error = Infallible>,
+{
+    /// Puts the panel to sleep (display off). The contents of the display RAM are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns the communication error and the display, still active.
+    pub async fn sleep(self) -> Result<Display<SPI, DC, RST, PO, Sleeping>, TransitionError<Self, E>> {
+        self.transition(Command::SetSleepMode(true)).await
+    }
+
+    /// Sets the command lock, so that the controller ignores every command until it is unlocked.
+    ///
+    /// # Errors
+    ///
+    /// Returns the communication error and the display, still active.
+    pub async fn lock(self) -> Result<Display<SPI, DC, RST, PO, Locked>, TransitionError<Self, E>> {
+        self.transition(Command::SetCommandLock(true)).await
+    }
+
+    /// Turns the panel off and switches VCC off. See `SSD1322::shutdown`.
+    ///
+    /// # Parameters
+    ///
+    /// - `delay`: A mutable reference to an implementation of the `DelayNs` trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns the communication error and the display, still active.
+    pub async fn shutdown<D>(
+        self,
+        delay: &mut D,
+    ) -> Result<Display<SPI, DC, RST, PO, PoweredOff>, TransitionError<Self, E>>
+    where
+        D: DelayNs,
+    {
+        self.power_transition(delay, false).await
+    }
+
+    /// Flushes a packed 4bpp buffer to the display. See `SSD1322::flush_buffer`.
+    pub async fn flush_buffer(&mut self, buf: &[u8]) -> Result<(), Error<E>> {
+        self.driver.flush_buffer(buf).await
+    }
+
+    /// Flushes a frame to the display. See `SSD1322::flush_frame`.
+    #[cfg(feature = "frame")]
+    pub async fn flush_frame<const N: usize>(&mut self, frame: &Frame<N>) -> Result<(), Error<E>> {
+        self.driver.flush_frame(frame).await
+    }
+
+    /// Writes rows into the scroll buffer, relative to the top of the panel. See
+    /// `SSD1322::write_rows`.
+    pub async fn write_rows(&mut self, y: u16, width: u16, data: &[u8]) -> Result<(), Error<E>> {
+        self.driver.write_rows(y, width, data).await
+    }
+
+    /// Scrolls the display to a row of the display RAM. See `SSD1322::scroll_to`.
+    pub async fn scroll_to(&mut self, row: u8) -> Result<(), Error<E>> {
+        self.driver.scroll_to(row).await
+    }
+
+    /// Scrolls the display by a number of rows. See `SSD1322::scroll_by`.
+    pub async fn scroll_by(&mut self, rows: i16) -> Result<(), Error<E>> {
+        self.driver.scroll_by(rows).await
+    }
+
+    /// Returns the row of the display RAM shown at the top of the panel. See
+    /// `SSD1322::scroll_position`.
+    pub fn scroll_position(&self) -> u8 {
+        self.driver.scroll_position()
+    }
+
+    /// Sets the orientation of the display. See `SSD1322::set_orientation`.
+    pub async fn set_orientation(&mut self, orientation: Orientation) -> Result<(), Error<E>> {
+        self.driver.set_orientation(orientation).await
+    }
+
+    /// Inverts the colours of the display, or restores them. See `SSD1322::set_inverted`.
+    pub async fn set_inverted(&mut self, inverted: bool) -> Result<(), Error<E>> {
+        self.driver.set_inverted(inverted).await
+    }
+
+    /// Blanks the display without changing the display RAM. See `SSD1322::blank`.
+    pub async fn blank(&mut self, mode: DisplayMode) -> Result<(), Error<E>> {
+        self.driver.blank(mode).await
+    }
+
+    /// Restores the image after `blank`. See `SSD1322::unblank`.
+    pub async fn unblank(&mut self) -> Result<(), Error<E>> {
+        self.driver.unblank().await
+    }
+
+    /// Returns the display mode the display is currently in. See `SSD1322::display_mode`.
+    pub fn display_mode(&self) -> DisplayMode {
+        self.driver.display_mode()
+    }
+
+    /// Sets the contrast current and master contrast registers. See `SSD1322::set_contrast`.
+    pub async fn set_contrast(&mut self, current: u8, master: u8) -> Result<(), Error<E>> {
+        self.driver.set_contrast(current, master).await
+    }
+
+    /// Returns the contrast current and master contrast registers. See `SSD1322::contrast`.
+    pub fn contrast(&self) -> (u8, u8) {
+        self.driver.contrast()
+    }
+
+    /// Sets the brightness of the display, 0-255. See `SSD1322::set_brightness`.
+    pub async fn set_brightness(&mut self, level: u8) -> Result<(), Error<E>> {
+        self.driver.set_brightness(level).await
+    }
+
+    /// Sets the brightness of the display as a percentage. See `SSD1322::set_brightness_percent`.
+    pub async fn set_brightness_percent(&mut self, percent: u8) -> Result<(), Error<E>> {
+        self.driver.set_brightness_percent(percent).await
+    }
+
+    /// Returns the brightness of the display, 0-255. See `SSD1322::brightness`.
+    pub fn brightness(&self) -> u8 {
+        self.driver.brightness()
+    }
+
+    /// Fades the brightness of the display to `level`. See `SSD1322::fade_to`.
+    pub async fn fade_to<D>(&mut self, level: u8, duration_ms: u32, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.driver.fade_to(level, duration_ms, delay).await
+    }
+
+    /// Leaves only a band of rows of the panel lit. See `SSD1322::set_active_rows`.
+    pub async fn set_active_rows(&mut self, rows: RangeInclusive<u8>) -> Result<(), Error<E>> {
+        self.driver.set_active_rows(rows).await
+    }
+
+    /// Lights the whole panel again. See `SSD1322::set_all_rows_active`.
+    pub async fn set_all_rows_active(&mut self) -> Result<(), Error<E>> {
+        self.driver.set_all_rows_active().await
+    }
+
+    /// Returns the rows of the panel left lit. See `SSD1322::active_rows`.
+    pub fn active_rows(&self) -> Option<RangeInclusive<u8>> {
+        self.driver.active_rows()
+    }
+
+    /// Reports time passing to the idle policy and burn-in protection. See `SSD1322::tick`.
+    pub async fn tick(&mut self, elapsed_ms: u32) -> Result<(), Error<E>> {
+        self.driver.tick(elapsed_ms).await
+    }
+
+    /// Waits, then reports the time to the idle policy and burn-in protection. See
+    /// `SSD1322::tick_delay`.
+    pub async fn tick_delay<D>(&mut self, delay: &mut D, ms: u32) -> Result<(), Error<E>>
+    where
+        D: DelayNs,
+    {
+        self.driver.tick_delay(delay, ms).await
+    }
+
+    /// Returns `true` if the idle policy has dimmed the display. See `SSD1322::is_idle`.
+    pub fn is_idle(&self) -> bool {
+        self.driver.is_idle()
+    }
+
+    /// Returns the current burn-in protection shift. See `SSD1322::burn_in_shift`.
+    pub fn burn_in_shift(&self) -> (u8, u8) {
+        self.driver.burn_in_shift()
+    }
+}
+
+impl<SPI, DC, RST, PO, E> Display<SPI, DC, RST, PO, Sleeping>
+where
+    SPI: SpiDevice<Error = E>,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    PO: OutputPin<Error = Infallible>,
+{
+    /// Wakes the panel (display on), and returns the display in the active state.
+    ///
+    /// # Errors
+    ///
+    /// Returns the communication error and the display, still asleep.
+    pub async fn wake(self) -> Result<Display<SPI, DC, RST, PO, Active>, TransitionError<Self, E>> {
+        self.transition(Command::SetSleepMode(false)).await
+    }
+
+    /// Switches VCC off. See `SSD1322::shutdown`.
+    ///
+    /// # Parameters
+    ///
+    /// - `delay`: A mutable reference to an implementation of the `DelayNs` trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns the communication error and the display, still asleep.
+    pub async fn shutdown<D>(
+        self,
+        delay: &mut D,
+    ) -> Result<Display<SPI, DC, RST, PO, PoweredOff>, TransitionError<Self, E>>
+    where
+        D: DelayNs,
+    {
+        self.power_transition(delay, false).await
+    }
+}
+
+impl<SPI, DC, RST, PO, E> Display<SPI, DC, RST, PO, Locked>
+where
+    SPI: SpiDevice<Error = E>,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    PO: OutputPin<Error = Infallible>,
+{
+    /// Clears the command lock, and returns the display in the active state.
+    ///
+    /// # Errors
+    ///
+    /// Returns the communication error and the display, still locked.
+    pub async fn unlock(self) -> Result<Display<SPI, DC, RST, PO, Active>, TransitionError<Self, E>> {
+        self.transition(Command::SetCommandLock(false)).await
+    }
+}
+
+impl<SPI, DC, RST, PO, E> Display<SPI, DC, RST, PO, PoweredOff>
+where
+    SPI: SpiDevice<Error = E>,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    PO: OutputPin<Error = Infallible>,
+{
+    /// Switches VCC back on and turns the panel on. See `SSD1322::power_on`.
+    ///
+    /// # Parameters
+    ///
+    /// - `delay`: A mutable reference to an implementation of the `DelayNs` trait.
+    ///
+    /// # Errors
+    ///
+    /// Returns the communication error and the display, still powered off.
+    pub async fn power_on<D>(
+        self,
+        delay: &mut D,
+    ) -> Result<Display<SPI, DC, RST, PO, Active>, TransitionError<Self, E>>
+    where
+        D: DelayNs,
+    {
+        self.power_transition(delay, true).await
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::{block_on, Recorder};
+    use crate::IdlePolicy;
+
+    #[test]
+    fn init_rejects_an_idle_policy_which_sleeps() {
+        let recorder = Recorder::new();
+        let config = Config {
+            idle_policy: Some(IdlePolicy {
+                dim_after_ms: None,
+                dim_brightness: 0,
+                sleep_after_ms: Some(1000),
+            }),
+            ..Config::default()
+        };
+        let display = Display::new(recorder.spi(), recorder.dc(), recorder.rst(), recorder.power(), config);
+        let Err(error) = block_on(display.init_default(&mut recorder.delay())) else {
+            panic!("a sleeping idle policy was accepted");
+        };
+        assert!(matches!(error.error, Error::CommandError(CommandError::OutOfRange)));
+        assert!(recorder.events().is_empty());
+    }
+
+    #[test]
+    fn active_display_forwards_flushes() {
+        let recorder = Recorder::new();
+        let display = Display::new(recorder.spi(), recorder.dc(), recorder.rst(), recorder.power(), Config::default());
+        let Ok(mut display) = block_on(display.init_default(&mut recorder.delay())) else {
+            panic!("init failed");
+        };
+        recorder.clear();
+        block_on(display.flush_buffer(&[0x11; 128 * 64])).unwrap();
+        assert!(recorder.commands().contains(&Command::WriteRam));
+    }
+}
@@ -0,0 +1,505 @@
+//! Wear accounting for burn-in compensation.
+//!
+//! An OLED pixel ages roughly in proportion to the time it is lit multiplied by its brightness.
+//! `WearMap` divides the panel into a coarse grid of cells, and accumulates the grey level of each
+//! pixel multiplied by the time it was shown into its cell. Over months and years the map shows
+//! which areas (for example a status bar) have worn more than others. It can be saved to and
+//! restored from flash, printed as a report, and used to compensate frames by slightly dimming
+//! the least worn areas, so that they age in step with the rest of the panel.
+//!
+//! The driver can keep a map itself: after `SSD1322::set_wear_map`, each frame flushed with
+//! `flush_frame` is accumulated for the time reported to `SSD1322::tick` while it is shown. A map
+//! with a different grid, or images written without `flush_frame`, can be accumulated by the
+//! application instead, when each frame is replaced or periodically for a static image.
+//!
+//! > **This module is only available when the `wear` feature is enabled.**
+//!
+//! # Example
+//!
+//! ```
+//! # use embedded_graphics_core::pixelcolor::{Gray4, GrayColor};
+//! use ssd1322_rs::wear::WearMap;
+//! use ssd1322_rs::Frame;
+//!
+//! // A 16x4 grid of 16x16 pixel cells over a 256x64 panel
+//! let mut wear = WearMap::<16, 4>::new(256, 64);
+//!
+//! // A status bar lit along the top of the panel for an hour
+//! let mut frame = Frame::new(256, 64, [0; 8192]);
+//! for x in 0..256 {
+//!     frame.set_pixel(x, 0, Gray4::WHITE);
+//! }
+//! wear.accumulate(&frame, 3_600_000);
+//! assert!(wear.cell(0, 0) > wear.cell(0, 1));
+//!
+//! // Save the map, and restore it after a reboot
+//! let mut bytes = [0; WearMap::<16, 4>::SERIALISED_LEN];
+//! wear.to_bytes(&mut bytes).unwrap();
+//! assert_eq!(WearMap::<16, 4>::from_bytes(&bytes), Ok(wear));
+//! ```
+
+use crate::frame::{Frame, ASCII_RAMP};
+use crate::SSD1322;
+use core::convert::Infallible;
+use core::fmt;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiDevice;
+
+/// Magic bytes at the start of a serialised `WearMap`.
+const MAGIC: &[u8; 4] = b"SSDW";
+/// Version of the serialised format.
+const VERSION: u8 = 1;
+/// Length of the serialised header: magic, version, width, height, columns and rows.
+const HEADER_LEN: usize = 4 + 1 + 2 * 4;
+
+/// The number of columns in the grid of the map kept by the driver.
+pub const DRIVER_COLS: usize = 16;
+/// The number of rows in the grid of the map kept by the driver.
+pub const DRIVER_ROWS: usize = 8;
+
+/// The map kept by the driver, see `SSD1322::set_wear_map`. Its 16x8 grid has 16x8 pixel cells on
+/// a 256x64 panel.
+pub type DriverWearMap = WearMap<DRIVER_COLS, DRIVER_ROWS>;
+
+/// Errors that can occur when saving or restoring a `WearMap`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WearError {
+    /// The buffer is too small for the serialised map.
+    BufferTooSmall,
+    /// The data does not start with the expected magic bytes and version.
+    BadHeader,
+    /// The data describes a grid with a different number of columns or rows.
+    SizeMismatch,
+}
+
+/// Accumulated wear of a panel, on a grid of `COLS` x `ROWS` cells.
+///
+/// The wear of a cell is the sum, over every pixel in the cell, of the grey level (0-15) of the
+/// pixel multiplied by the time it was shown, in milliseconds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WearMap<const COLS: usize, const ROWS: usize> {
+    width: u16,
+    height: u16,
+    cells: [[u64; COLS]; ROWS],
+}
+
+impl<const COLS: usize, const ROWS: usize> WearMap<COLS, ROWS> {
+    /// The length of a serialised map, in bytes.
+    pub const SERIALISED_LEN: usize = HEADER_LEN + COLS * ROWS * 8;
+
+    // The grid must have cells, and its size must fit in the serialised header
+    const GRID_SIZE_OK: () = assert!(
+        COLS > 0 && ROWS > 0 && COLS <= u16::MAX as usize && ROWS <= u16::MAX as usize,
+        "WearMap needs 1 to 65535 columns and rows"
+    );
+
+    /// Creates a new map, with no wear, for a panel of the given size.
+    ///
+    /// `COLS` and `ROWS` must be between 1 and 65535, which is checked at compile time.
+    ///
+    /// # Parameters
+    ///
+    /// - `width`: The width of the panel in pixels.
+    /// - `height`: The height of the panel in pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is 0.
+    pub fn new(width: u16, height: u16) -> Self {
+        let () = Self::GRID_SIZE_OK;
+        assert!(width > 0 && height > 0, "WearMap needs a panel of at least 1x1 pixels");
+        Self {
+            width,
+            height,
+            cells: [[0; COLS]; ROWS],
+        }
+    }
+
+    /// Returns the cell a pixel belongs to, as a column and row.
+    fn cell_of(&self, x: u16, y: u16) -> (usize, usize) {
+        (
+            x as usize * COLS / self.width as usize,
+            y as usize * ROWS / self.height as usize,
+        )
+    }
+
+    /// Adds the wear of showing a frame for a period of time.
+    ///
+    /// Pixels outside of the panel are ignored.
+    ///
+    /// # Parameters
+    ///
+    /// - `frame`: The frame shown.
+    /// - `ms`: How long the frame was shown for, in milliseconds.
+    pub fn accumulate<const N: usize>(&mut self, frame: &Frame<N>, ms: u32) {
+        let levels = self.cell_levels(frame);
+        self.add_levels(&levels, ms);
+    }
+
+    /// Returns the sum of the grey levels of the frame's pixels in each cell, ignoring pixels
+    /// outside of the panel.
+    fn cell_levels<const N: usize>(&self, frame: &Frame<N>) -> [[u32; COLS]; ROWS] {
+        let mut levels = [[0; COLS]; ROWS];
+        let width = frame.width.min(self.width);
+        let height = frame.height.min(self.height);
+        for y in 0..height {
+            for x in 0..width {
+                let level = frame.level(x, y).unwrap_or(0);
+                if level != 0 {
+                    let (col, row) = self.cell_of(x, y);
+                    levels[row][col] += level as u32;
+                }
+            }
+        }
+        levels
+    }
+
+    /// Adds the wear of showing pixels whose grey levels sum to `levels` in each cell for `ms`
+    /// milliseconds.
+    fn add_levels(&mut self, levels: &[[u32; COLS]; ROWS], ms: u32) {
+        for (cells, levels) in self.cells.iter_mut().zip(levels) {
+            for (cell, &level) in cells.iter_mut().zip(levels) {
+                *cell = cell.saturating_add(level as u64 * ms as u64);
+            }
+        }
+    }
+
+    /// Returns the wear of a cell, or 0 if the cell is outside of the grid.
+    ///
+    /// # Parameters
+    ///
+    /// - `col`: The column of the cell.
+    /// - `row`: The row of the cell.
+    pub fn cell(&self, col: usize, row: usize) -> u64 {
+        self.cells.get(row).and_then(|cells| cells.get(col)).copied().unwrap_or(0)
+    }
+
+    /// Returns the wear of every cell, row by row.
+    pub fn cells(&self) -> &[[u64; COLS]; ROWS] {
+        &self.cells
+    }
+
+    /// Returns the wear of the most worn cell.
+    pub fn max(&self) -> u64 {
+        self.cells.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// Returns the wear of the least worn cell.
+    pub fn min(&self) -> u64 {
+        self.cells.iter().flatten().copied().min().unwrap_or(0)
+    }
+
+    /// Resets the wear of every cell to zero.
+    pub fn clear(&mut self) {
+        self.cells = [[0; COLS]; ROWS];
+    }
+
+    /// Dims the less worn areas of a frame, so that they wear in step with the most worn cell.
+    ///
+    /// Each pixel is dimmed in proportion to how much less worn its cell is than the most worn
+    /// cell, and to its own grey level, so that black stays black. A pixel at level 15 in a cell
+    /// with no wear is dimmed by `max_dim` levels; a pixel in the most worn cell is not dimmed.
+    ///
+    /// # Parameters
+    ///
+    /// - `frame`: The frame to compensate, usually just before it is flushed.
+    /// - `max_dim`: The most grey levels any pixel is dimmed by, 0-15. A level or two is enough
+    ///   to even out wear without visibly changing the image.
+    pub fn compensate<const N: usize>(&self, frame: &mut Frame<N>, max_dim: u8) {
+        let max = self.max();
+        if max == 0 || max_dim == 0 {
+            return;
+        }
+        let width = frame.width.min(self.width);
+        let height = frame.height.min(self.height);
+        for y in 0..height {
+            for x in 0..width {
+                let Some(level) = frame.level(x, y).filter(|&level| level != 0) else {
+                    continue;
+                };
+                let (col, row) = self.cell_of(x, y);
+                let deficit = (max - self.cells[row][col]) as u128;
+                let dim = (level as u128 * max_dim.min(15) as u128 * deficit + 15 * max as u128 / 2)
+                    / (15 * max as u128);
+                frame.set_level(x, y, level.saturating_sub(dim as u8));
+            }
+        }
+    }
+
+    /// Serialises the map into a buffer of at least `SERIALISED_LEN` bytes, as little endian.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written, which is always `SERIALISED_LEN`.
+    ///
+    /// # Errors
+    ///
+    /// - `WearError::BufferTooSmall`: If `buf` is shorter than `SERIALISED_LEN`.
+    pub fn to_bytes(&self, buf: &mut [u8]) -> Result<usize, WearError> {
+        let buf = buf.get_mut(..Self::SERIALISED_LEN).ok_or(WearError::BufferTooSmall)?;
+        let (header, body) = buf.split_at_mut(HEADER_LEN);
+        header[..4].copy_from_slice(MAGIC);
+        header[4] = VERSION;
+        header[5..7].copy_from_slice(&self.width.to_le_bytes());
+        header[7..9].copy_from_slice(&self.height.to_le_bytes());
+        header[9..11].copy_from_slice(&(COLS as u16).to_le_bytes());
+        header[11..13].copy_from_slice(&(ROWS as u16).to_le_bytes());
+        for (chunk, cell) in body.chunks_exact_mut(8).zip(self.cells.iter().flatten()) {
+            chunk.copy_from_slice(&cell.to_le_bytes());
+        }
+        Ok(Self::SERIALISED_LEN)
+    }
+
+    /// Restores a map serialised with `to_bytes`.
+    ///
+    /// # Errors
+    ///
+    /// - `WearError::BadHeader`: If `bytes` doesn't start with a serialised map header, or the
+    ///   header describes a panel 0 pixels wide or high.
+    /// - `WearError::SizeMismatch`: If the map was serialised with a different grid size.
+    /// - `WearError::BufferTooSmall`: If `bytes` ends before the last cell.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WearError> {
+        let header = bytes.get(..HEADER_LEN).ok_or(WearError::BadHeader)?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(WearError::BadHeader);
+        }
+        let read_u16 = |at: usize| u16::from_le_bytes([header[at], header[at + 1]]);
+        if read_u16(5) == 0 || read_u16(7) == 0 {
+            return Err(WearError::BadHeader);
+        }
+        if read_u16(9) as usize != COLS || read_u16(11) as usize != ROWS {
+            return Err(WearError::SizeMismatch);
+        }
+        let body = bytes
+            .get(HEADER_LEN..Self::SERIALISED_LEN)
+            .ok_or(WearError::BufferTooSmall)?;
+
+        let mut map = Self::new(read_u16(5), read_u16(7));
+        for (cell, chunk) in map.cells.iter_mut().flatten().zip(body.chunks_exact(8)) {
+            let mut le = [0; 8];
+            le.copy_from_slice(chunk);
+            *cell = u64::from_le_bytes(le);
+        }
+        Ok(map)
+    }
+}
+
+/// The wear map kept by the driver, with the grey levels of the frame being shown.
+pub(crate) struct WearTracker {
+    map: DriverWearMap,
+    /// The sum of the grey levels in each cell of the frame last flushed.
+    levels: [[u32; DRIVER_COLS]; DRIVER_ROWS],
+}
+
+impl<SPI, DC, RST, PO, E> SSD1322<SPI, DC, RST, PO>
+where
+    SPI: SpiDevice<Error = E>,
+    DC: OutputPin<Error = Infallible>,
+    RST: OutputPin<Error = Infallible>,
+    PO: OutputPin<Error = Infallible>,
+{
+    /// Sets the map the driver accumulates wear into, or `None` to stop tracking wear, and
+    /// returns the previous map.
+    ///
+    /// Each frame flushed with `flush_frame` is accumulated into the map for the time reported
+    /// to `tick` until the next one, as `WearMap::accumulate` would. Time while the panel is
+    /// asleep or blanked adds no wear, and images written with `flush_buffer`, `write_rows` or
+    /// `init_with_splash` aren't tracked. The map starts counting with the next frame flushed.
+    ///
+    /// # Parameters
+    ///
+    /// - `map`: The map to accumulate into, for example one restored with
+    ///   `WearMap::from_bytes` after a reboot.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use ssd1322_rs::{SSD1322, Error, Frame};
+    /// # use ssd1322_rs::wear::DriverWearMap;
+    /// # use core::convert::Infallible;
+    /// # use embedded_hal::digital::OutputPin;
+    /// # use embedded_hal_async::spi::SpiDevice;
+    /// # async fn example_usage<SPI, DC, RST, PO, E>(display: &mut SSD1322<SPI, DC, RST, PO>, frame: &Frame<8192>, saved: &[u8]) -> Result<(), Error<E>>
+    /// # where
+    /// #     SPI: SpiDevice<Error = E>,
+    /// #     DC: OutputPin<Error = Infallible>,
+    /// #     RST: OutputPin<Error = Infallible>,
+    /// #     PO: OutputPin<Error = Infallible>,
+    /// # {
+    /// // Carry on from the wear saved before the last reboot
+    /// let map = DriverWearMap::from_bytes(saved).unwrap_or_else(|_| DriverWearMap::new(256, 64));
+    /// display.set_wear_map(Some(map));
+    ///
+    /// // In the application's main loop, which runs every 100ms
+    /// display.flush_frame(frame).await?;
+    /// display.tick(100).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_wear_map(&mut self, map: Option<DriverWearMap>) -> Option<DriverWearMap> {
+        let tracker = map.map(|map| WearTracker { map, levels: [[0; DRIVER_COLS]; DRIVER_ROWS] });
+        core::mem::replace(&mut self.wear, tracker).map(|tracker| tracker.map)
+    }
+
+    /// Returns the map the driver accumulates wear into, if any. See `set_wear_map`.
+    pub fn wear_map(&self) -> Option<&DriverWearMap> {
+        self.wear.as_ref().map(|tracker| &tracker.map)
+    }
+
+    // Records the frame now shown, for the wear map
+    pub(crate) fn record_wear<const N: usize>(&mut self, frame: &Frame<N>) {
+        if let Some(tracker) = &mut self.wear {
+            tracker.levels = tracker.map.cell_levels(frame);
+        }
+    }
+
+    // Forgets the frame shown, after the display RAM is overwritten
+    pub(crate) fn forget_wear(&mut self) {
+        if let Some(tracker) = &mut self.wear {
+            tracker.levels = [[0; DRIVER_COLS]; DRIVER_ROWS];
+        }
+    }
+
+    // Adds the wear of showing the frame for `elapsed_ms`, unless the panel is dark
+    pub(crate) fn accumulate_wear(&mut self, elapsed_ms: u32) {
+        let dark = self.idle_asleep || self.blanked.is_some();
+        if let Some(tracker) = self.wear.as_mut().filter(|_| !dark) {
+            tracker.map.add_levels(&tracker.levels, elapsed_ms);
+        }
+    }
+}
+
+/// Renders the map as text, one character per cell using `ASCII_RAMP`, scaled so that the most
+/// worn cell is the brightest character.
+impl<const COLS: usize, const ROWS: usize> fmt::Display for WearMap<COLS, ROWS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let max = self.max().max(1) as u128;
+        for row in &self.cells {
+            for &cell in row {
+                let level = (cell as u128 * 15).div_ceil(max) as usize;
+                fmt::Write::write_char(f, ASCII_RAMP[level] as char)?;
+            }
+            fmt::Write::write_char(f, '\n')?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics_core::pixelcolor::{Gray4, GrayColor};
+
+    fn worn_map() -> WearMap<2, 2> {
+        let mut wear = WearMap::new(8, 8);
+        let mut frame = Frame::new(8, 8, [0; 32]);
+        frame.set_pixel(0, 0, Gray4::WHITE);
+        frame.set_pixel(7, 7, Gray4::new(5));
+        wear.accumulate(&frame, 1000);
+        wear
+    }
+
+    #[test]
+    fn accumulates_level_times_duration_per_cell() {
+        let wear = worn_map();
+        assert_eq!(wear.cells(), &[[15_000, 0], [0, 5_000]]);
+    }
+
+    #[test]
+    fn serialised_map_round_trips() {
+        let wear = worn_map();
+        let mut bytes = [0; WearMap::<2, 2>::SERIALISED_LEN];
+        assert_eq!(wear.to_bytes(&mut bytes), Ok(WearMap::<2, 2>::SERIALISED_LEN));
+        assert_eq!(WearMap::<2, 2>::from_bytes(&bytes), Ok(wear.clone()));
+
+        assert_eq!(WearMap::<2, 1>::from_bytes(&bytes), Err(WearError::SizeMismatch));
+        assert_eq!(WearMap::<2, 2>::from_bytes(&bytes[..bytes.len() - 1]), Err(WearError::BufferTooSmall));
+        assert_eq!(wear.to_bytes(&mut [0; 8]), Err(WearError::BufferTooSmall));
+    }
+
+    #[test]
+    fn zero_sized_panel_is_a_bad_header() {
+        let mut bytes = [0; WearMap::<2, 2>::SERIALISED_LEN];
+        worn_map().to_bytes(&mut bytes).unwrap();
+        bytes[5..7].copy_from_slice(&0u16.to_le_bytes());
+        assert_eq!(WearMap::<2, 2>::from_bytes(&bytes), Err(WearError::BadHeader));
+
+        bytes[..4].copy_from_slice(b"XXXX");
+        assert_eq!(WearMap::<2, 2>::from_bytes(&bytes), Err(WearError::BadHeader));
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_sized_panel_is_rejected() {
+        WearMap::<2, 2>::new(0, 8);
+    }
+
+    #[test]
+    fn compensation_dims_less_worn_cells_in_proportion() {
+        let wear = worn_map();
+        let mut frame = Frame::new(8, 8, [0xFF; 32]);
+        wear.compensate(&mut frame, 3);
+        // The most worn cell isn't dimmed, an unworn one is dimmed fully, and the rest in
+        // proportion to their deficit
+        assert_eq!(frame.level(0, 0), Some(15));
+        assert_eq!(frame.level(7, 0), Some(12));
+        assert_eq!(frame.level(7, 7), Some(13));
+
+        let mut frame = Frame::new(8, 8, [0; 32]);
+        frame.set_pixel(7, 0, Gray4::new(5));
+        wear.compensate(&mut frame, 3);
+        assert_eq!(frame.level(7, 0), Some(4));
+        assert_eq!(frame.level(6, 0), Some(0));
+    }
+
+    #[test]
+    fn unworn_map_leaves_frames_alone() {
+        let mut frame = Frame::new(8, 8, [0xFF; 32]);
+        WearMap::<2, 2>::new(8, 8).compensate(&mut frame, 15);
+        assert_eq!(frame.level(3, 3), Some(15));
+    }
+    #[cfg(feature = "testing")]
+    mod driver {
+        use super::*;
+        use crate::testing::{block_on, Recorder};
+        use crate::Config;
+        use crate::IdlePolicy;
+
+        #[test]
+        fn flushed_frames_wear_for_the_time_ticked() {
+            let recorder = Recorder::new();
+            let idle_policy = IdlePolicy { dim_after_ms: None, dim_brightness: 0, sleep_after_ms: Some(3000) };
+            let mut display = recorder.display(Config { idle_policy: Some(idle_policy), ..Config::default() });
+            block_on(display.init_default(&mut recorder.delay())).unwrap();
+            assert!(display.set_wear_map(Some(DriverWearMap::new(256, 64))).is_none());
+
+            // Nothing is worn before the first frame
+            block_on(display.tick(500)).unwrap();
+            assert_eq!(display.wear_map().unwrap().max(), 0);
+
+            let mut frame = Frame::new(256, 64, [0; 8192]);
+            frame.set_pixel(0, 0, Gray4::WHITE);
+            frame.set_pixel(255, 63, Gray4::new(5));
+            block_on(display.flush_frame(&frame)).unwrap();
+            block_on(display.tick(1000)).unwrap();
+            block_on(display.tick(1000)).unwrap();
+            let wear = display.wear_map().unwrap();
+            assert_eq!(wear.cell(0, 0), 30_000);
+            assert_eq!(wear.cell(15, 7), 10_000);
+
+            // The next frame replaces the first
+            block_on(display.flush_frame(&Frame::new(256, 64, [0; 8192]))).unwrap();
+            block_on(display.tick(1000)).unwrap();
+            assert_eq!(display.wear_map().unwrap().cell(0, 0), 30_000);
+
+            // A sleeping panel doesn't wear
+            block_on(display.flush_frame(&frame)).unwrap();
+            block_on(display.tick(3000)).unwrap();
+            block_on(display.tick(1000)).unwrap();
+            let wear = display.set_wear_map(None).unwrap();
+            assert_eq!(wear.cell(0, 0), 75_000);
+            assert!(display.wear_map().is_none());
+        }
+    }
+}